@@ -0,0 +1,238 @@
+// Benchmarks comparing our generic linked list against the standard library's Vec<T> and
+// LinkedList<T>. Run them with `cargo bench` and open target/criterion/report/index.html to
+// inspect the results.
+//
+// Every benchmark runs for all three types and for N = 100, 10_000 and 1_000_000 elements, so we
+// can see where the hand-rolled list wins or loses as N grows. (hash_map_get is the odd one out:
+// it compares the List-based hash map with std's HashMap.)
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use lists::generic_lists::List;
+use lists::hash_map;
+use std::collections::{HashMap, LinkedList};
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [100, 10_000, 1_000_000];
+
+// A tiny xorshift generator so the sort benchmarks get the same "random" input every run
+// without pulling in the rand crate.
+fn random_ints(n: usize) -> Vec<i32> {
+    let mut state: u32 = 0x2545_f491;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as i32
+        })
+        .collect()
+}
+
+fn build_list(n: usize) -> List<i32> {
+    let mut list = List::new();
+    for i in 0..n as i32 {
+        list.push(i);
+    }
+    list
+}
+
+fn bench_push(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::new("List", n), &n, |b, &n| {
+            b.iter(|| build_list(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("Vec", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut vec = Vec::new();
+                for i in 0..black_box(n) as i32 {
+                    vec.push(i);
+                }
+                vec
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = LinkedList::new();
+                for i in 0..black_box(n) as i32 {
+                    list.push_front(i);
+                }
+                list
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pop");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::new("List", n), &n, |b, &n| {
+            b.iter_batched(
+                || build_list(n),
+                |mut list| {
+                    while let Some(elem) = list.pop() {
+                        black_box(elem);
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("Vec", n), &n, |b, &n| {
+            b.iter_batched(
+                || (0..n as i32).collect::<Vec<_>>(),
+                |mut vec| {
+                    while let Some(elem) = vec.pop() {
+                        black_box(elem);
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &n, |b, &n| {
+            b.iter_batched(
+                || (0..n as i32).collect::<LinkedList<_>>(),
+                |mut list| {
+                    while let Some(elem) = list.pop_front() {
+                        black_box(elem);
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let list = build_list(n);
+        group.bench_with_input(BenchmarkId::new("List", n), &list, |b, list| {
+            b.iter(|| list.iter().fold(0i64, |acc, &x| acc + x as i64))
+        });
+        let vec: Vec<i32> = (0..n as i32).collect();
+        group.bench_with_input(BenchmarkId::new("Vec", n), &vec, |b, vec| {
+            b.iter(|| vec.iter().fold(0i64, |acc, &x| acc + x as i64))
+        });
+        let linked: LinkedList<i32> = (0..n as i32).collect();
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &linked, |b, linked| {
+            b.iter(|| linked.iter().fold(0i64, |acc, &x| acc + x as i64))
+        });
+    }
+    group.finish();
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let input = random_ints(n);
+        group.bench_with_input(BenchmarkId::new("List", n), &input, |b, input| {
+            b.iter_batched(
+                || {
+                    let mut list = List::new();
+                    for &x in input {
+                        list.push(x);
+                    }
+                    list
+                },
+                |mut list| {
+                    list.sort_in_place();
+                    list
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("Vec", n), &input, |b, input| {
+            b.iter_batched(
+                || input.clone(),
+                |mut vec| {
+                    vec.sort_unstable();
+                    vec
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        // LinkedList has no sort of its own, so it goes through a Vec and back.
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &input, |b, input| {
+            b.iter_batched(
+                || input.iter().copied().collect::<LinkedList<_>>(),
+                |list| {
+                    let mut elems: Vec<i32> = list.into_iter().collect();
+                    elems.sort_unstable();
+                    elems.into_iter().collect::<LinkedList<_>>()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+// The three sorts on List against each other, on one size only: the O(n^2) ones would take
+// forever on the bigger inputs. Even at 1000 elements bubble sort should be far behind the merge
+// sort.
+type ListSort = fn(&mut List<i32>);
+
+fn bench_list_sorts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("list_sorts");
+    group.sample_size(10);
+    let input = random_ints(1000);
+    let sorts: [(&str, ListSort); 3] = [
+        ("merge", List::sort_in_place),
+        ("selection", List::selection_sort),
+        ("bubble", List::bubble_sort),
+    ];
+    for (name, sort) in sorts.iter() {
+        group.bench_with_input(BenchmarkId::new(*name, input.len()), &input, |b, input| {
+            b.iter_batched(
+                || input.iter().copied().collect::<List<_>>(),
+                |mut list| {
+                    sort(&mut list);
+                    list
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+// Looking up every key once in our chained hash_map::HashMap and in std's HashMap, both filled
+// with the same keys. Both hash with SipHash (RandomState), so the difference is in how they
+// find an entry: following list nodes in a bucket against probing one flat array.
+fn bench_hash_map_get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_map_get");
+    group.sample_size(10);
+    for &n in SIZES.iter() {
+        let keys: Vec<i32> = random_ints(n);
+        let mut ours = hash_map::HashMap::new();
+        let mut std_map = HashMap::new();
+        for &key in &keys {
+            ours.insert(key, key);
+            std_map.insert(key, key);
+        }
+        group.bench_with_input(BenchmarkId::new("chained", n), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter_map(|key| ours.get(key)).map(|&x| x as i64).sum::<i64>())
+        });
+        group.bench_with_input(BenchmarkId::new("std", n), &keys, |b, keys| {
+            b.iter(|| keys.iter().filter_map(|key| std_map.get(key)).map(|&x| x as i64).sum::<i64>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_push,
+    bench_pop,
+    bench_iter,
+    bench_sort,
+    bench_list_sorts,
+    bench_hash_map_get
+);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+// Classic puzzles and algorithms solved with the list types in this crate.
+
+use crate::circular_list::CircularList;
+use crate::stack::Stack;
+
+// The Josephus problem: people stand in a circle and, starting from the first one, every
+// `step`-th person is eliminated until only one is left. Returns the survivor.
+//
+// A circular list models this directly: skip `step - 1` people, remove the current one (the next
+// person becomes current), and repeat. Panics if the circle is empty or `step` is 0.
+pub fn josephus<T>(mut list: CircularList<T>, step: usize) -> T {
+    assert!(step > 0, "josephus step must be at least 1");
+    assert!(!list.is_empty(), "josephus needs at least one person");
+
+    while list.len() > 1 {
+        list.advance(step - 1);
+        list.remove_current();
+    }
+    list.remove_current().unwrap()
+}
+
+// Whether every (, [ and { in `input` is closed by the matching bracket, in the right order.
+// Any other characters are ignored, so "f(a[0])" counts as balanced.
+//
+// The brackets that are still open form a stack: the one opened last has to be closed first. An
+// opening bracket is pushed, and a closing one has to match the bracket on top, which is then
+// done with. Whatever is left open at the end means a missing closing bracket.
+pub fn is_balanced(input: &str) -> bool {
+    let mut open = Stack::new();
+    for c in input.chars() {
+        let opener = match c {
+            '(' | '[' | '{' => {
+                open.push(c);
+                continue;
+            }
+            ')' => '(',
+            ']' => '[',
+            '}' => '{',
+            _ => continue,
+        };
+        if open.pop() != Some(opener) {
+            return false;
+        }
+    }
+    open.is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_balanced, josephus};
+    use crate::circular_list::CircularList;
+
+    fn circle(n: usize) -> CircularList<usize> {
+        let mut list = CircularList::new();
+        for person in 0..n {
+            list.push_back(person);
+        }
+        list
+    }
+
+    // The textbook recurrence J(1) = 0, J(n) = (J(n - 1) + k) mod n, 0-indexed.
+    fn reference(n: usize, k: usize) -> usize {
+        (2..=n).fold(0, |survivor, i| (survivor + k) % i)
+    }
+
+    #[test]
+    fn seven_people_step_three() {
+        // Eliminated in order 2, 5, 1, 6, 4, 0, leaving person 3 (0-indexed). Numbering the
+        // people from 1 instead gives the classic textbook answer of 4.
+        assert_eq!(josephus(circle(7), 3), 3);
+
+        let mut one_indexed = CircularList::new();
+        for person in 1..=7 {
+            one_indexed.push_back(person);
+        }
+        assert_eq!(josephus(one_indexed, 3), 4);
+    }
+
+    #[test]
+    fn single_person() {
+        assert_eq!(josephus(circle(1), 1), 0);
+
+        let mut list = CircularList::new();
+        list.push_back("only one");
+        assert_eq!(josephus(list, 5), "only one");
+    }
+
+    #[test]
+    fn hundred_people_step_seven() {
+        assert_eq!(josephus(circle(100), 7), 49);
+        assert_eq!(josephus(circle(100), 7), reference(100, 7));
+        assert_eq!(josephus(circle(41), 3), reference(41, 3));
+    }
+
+    #[test]
+    fn balanced_brackets() {
+        assert!(is_balanced("([{}])"));
+        assert!(is_balanced(""));
+        assert!(is_balanced("()[]{}"));
+        assert!(is_balanced("fn main() { let v = vec![(1, 2)]; }"));
+
+        // Closed in the wrong order, or by the wrong kind:
+        assert!(!is_balanced("([)]"));
+        assert!(!is_balanced("(]"));
+        // Starts out balanced, then has extras left open or closed:
+        assert!(!is_balanced("([]){"));
+        assert!(!is_balanced("([]))"));
+        assert!(!is_balanced("}{"));
+    }
+
+    #[test]
+    fn deeply_nested_brackets() {
+        let depth = if cfg!(miri) { 100 } else { 10_000 };
+        let nested = "([{".repeat(depth) + &"}])".repeat(depth);
+        assert!(is_balanced(&nested));
+        // One closing bracket short:
+        assert!(!is_balanced(&nested[..nested.len() - 1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "josephus step must be at least 1")]
+    fn zero_step() {
+        josephus(circle(3), 0);
+    }
+}
@@ -0,0 +1,129 @@
+// An association list: a linked list of (key, value) pairs. It's the map of functional languages
+// (Lisp's alist, Haskell's `lookup`), with none of the hashing or ordering a HashMap or BTreeMap
+// needs, only K: PartialEq.
+//
+// New bindings are pushed on the front, and lookups walk from the front and stop at the first
+// match. So binding a key that is already there doesn't overwrite it, it *shadows* it: the new
+// binding is found first, and removing it brings the old one back. That's exactly how nested
+// scopes in a symbol table behave. Everything is O(n), which is fine for the short lists this is
+// meant for.
+
+use crate::generic_lists::List;
+
+pub struct AList<K, V>(List<(K, V)>);
+
+impl<K, V> AList<K, V> {
+    pub fn new() -> Self {
+        AList(List::new())
+    }
+
+    // The number of bindings, shadowed ones included.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Binds `key` to `value` by prepending, shadowing any earlier binding of `key`.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.0.push((key, value));
+    }
+
+    // The value of the newest binding of `key`.
+    pub fn lookup(&self, key: &K) -> Option<&V>
+    where
+        K: PartialEq,
+    {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    // Removes the newest binding of `key` and returns its value. A binding it was shadowing
+    // becomes visible again.
+    pub fn remove(&mut self, key: &K) -> Option<V>
+    where
+        K: PartialEq,
+    {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    // Overwrites the value of the newest binding of `key` in place, without adding a binding.
+    // Returns false (and drops `value`) if `key` isn't bound.
+    pub fn update(&mut self, key: &K, value: V) -> bool
+    where
+        K: PartialEq,
+    {
+        match self.0.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => {
+                *v = value;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<K, V> Default for AList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AList;
+
+    #[test]
+    fn symbol_table() {
+        let mut symbols = AList::new();
+        assert_eq!(symbols.lookup(&"x"), None);
+
+        symbols.insert("x", 1);
+        symbols.insert("y", 2);
+        assert_eq!(symbols.lookup(&"x"), Some(&1));
+        assert_eq!(symbols.lookup(&"y"), Some(&2));
+        assert_eq!(symbols.lookup(&"z"), None);
+
+        assert!(symbols.update(&"y", 20));
+        assert_eq!(symbols.lookup(&"y"), Some(&20));
+        assert!(!symbols.update(&"z", 30));
+        assert_eq!(symbols.lookup(&"z"), None);
+        assert_eq!(symbols.len(), 2);
+
+        assert_eq!(symbols.remove(&"x"), Some(1));
+        assert_eq!(symbols.lookup(&"x"), None);
+        assert_eq!(symbols.remove(&"x"), None);
+        assert_eq!(symbols.len(), 1);
+    }
+
+    #[test]
+    fn shadowing() {
+        // Like `let x = 1; let y = 5; { let x = 2; { let x = 3; ... } }` in nested scopes:
+        let mut scope = AList::new();
+        scope.insert("x", 1);
+        scope.insert("y", 5);
+        scope.insert("x", 2);
+        scope.insert("x", 3);
+
+        // The newest binding wins, but the old ones are still there:
+        assert_eq!(scope.lookup(&"x"), Some(&3));
+        assert_eq!(scope.len(), 4);
+
+        // Updating only touches the visible binding:
+        assert!(scope.update(&"x", 30));
+        assert_eq!(scope.remove(&"x"), Some(30));
+
+        // Leaving each scope uncovers the binding it shadowed:
+        assert_eq!(scope.lookup(&"x"), Some(&2));
+        assert_eq!(scope.remove(&"x"), Some(2));
+        assert_eq!(scope.lookup(&"x"), Some(&1));
+        assert_eq!(scope.remove(&"x"), Some(1));
+        assert_eq!(scope.lookup(&"x"), None);
+
+        // Other keys weren't affected:
+        assert_eq!(scope.lookup(&"y"), Some(&5));
+        assert_eq!(scope.len(), 1);
+    }
+}
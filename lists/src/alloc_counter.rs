@@ -0,0 +1,65 @@
+// A counting allocator for the tests, to make the cost of a linked list's heap allocations
+// visible: every push allocates a node, Vec only allocates when it grows.
+//
+// It's installed as the global allocator of the test binary and passes every request on to the
+// system allocator, counting allocations and live bytes on the side. The counters are
+// thread-local because the test harness runs tests on several threads at once; each test only
+// sees what its own thread allocated.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    // `const` initializers, so touching the counters never allocates (which would recurse).
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+// try_with, because a thread that is shutting down may still free memory after its
+// thread-locals are gone.
+fn record(allocations: usize, bytes: isize) {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + allocations));
+    let _ = LIVE_BYTES.try_with(|live| live.set(live.get() + bytes));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(1, layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(0, -(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+    // realloc isn't overridden: the default one goes through alloc and dealloc above.
+}
+
+// What the current thread has allocated so far. Take one before and one after the code you are
+// interested in and subtract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    pub allocations: usize,
+    pub live_bytes: isize,
+}
+
+pub fn snapshot() -> Snapshot {
+    Snapshot { allocations: ALLOCATIONS.with(Cell::get), live_bytes: LIVE_BYTES.with(Cell::get) }
+}
+
+// The allocations made and the change in live bytes while running `f`.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Snapshot) {
+    let before = snapshot();
+    let result = f();
+    let after = snapshot();
+    let delta = Snapshot {
+        allocations: after.allocations - before.allocations,
+        live_bytes: after.live_bytes - before.live_bytes,
+    };
+    (result, delta)
+}
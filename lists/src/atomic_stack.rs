@@ -0,0 +1,287 @@
+// A lock-free stack: many threads can push and pop at the same time without a Mutex.
+//
+// The stack is our usual singly linked list, except the head is an AtomicPtr. To push we build a
+// node pointing at the current head and try to swing the head over to it with compare-and-swap
+// (CAS): "if head is still what I read, make it my node". If another thread got there first the
+// CAS fails, we re-read the head and try again. Popping works the same way, swinging the head to
+// the second node.
+//
+// The hard part is freeing popped nodes. Between reading the head and doing the CAS, a popping
+// thread dereferences the head node (to find `next`). If another thread popped and freed that
+// node in the meantime we would read freed memory. Worse, the allocator could hand the same
+// address to a brand new node, the head pointer would look unchanged and our CAS would succeed
+// with a stale `next`: the ABA problem.
+//
+// The fix used here is deferred freeing (from "C++ Concurrency in Action"): `threads_inside`
+// counts the threads currently inside push or pop. A popped node is only freed right away if the
+// popping thread is the only one inside; otherwise it goes on a `to_be_deleted` list that is freed
+// later by whichever popper next finds itself alone. While any push or pop is running no node it
+// might be looking at can be freed, so neither use-after-free nor ABA can happen.
+//
+// The book only counts poppers. A pusher never dereferences the head it read, but it does store
+// it in its node's `next`: if that node is freed and the allocator hands the address to a new
+// head, the pusher's CAS succeeds and links in a pointer to the old, freed node. The address is
+// the same, so on real hardware nothing goes wrong, but in Rust that pointer is still dangling
+// (Miri catches it), so pushers are counted too.
+
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+pub struct AtomicStack<T> {
+    head: AtomicPtr<Node<T>>,
+    threads_inside: AtomicUsize,
+    to_be_deleted: AtomicPtr<Node<T>>,
+}
+
+struct Node<T> {
+    // The element is moved out by the thread that wins the pop, long before the node itself is
+    // freed, so the node must not drop it again.
+    elem: ManuallyDrop<T>,
+    next: *mut Node<T>,
+    // Links popped nodes on the to_be_deleted list. This can't reuse `next`: a slower popper may
+    // still be reading `next` of a node that was already popped.
+    pending_next: *mut Node<T>,
+}
+
+// SAFETY: the stack owns its elements and only ever moves them in (push) and out (pop), it never
+// hands out references to them. Sending the whole stack to another thread therefore just moves
+// T values across threads, which is fine when T: Send.
+unsafe impl<T: Send> Send for AtomicStack<T> {}
+
+// SAFETY: sharing &AtomicStack between threads lets every thread push and pop, so a T pushed on
+// one thread may be popped on another: that again only needs T: Send (the same reasoning as
+// Mutex<T>, which is Sync when T: Send). All shared state is accessed through atomics, and nodes
+// are freed only when no other thread can still be reading them (see the comment at the top).
+unsafe impl<T: Send> Sync for AtomicStack<T> {}
+
+impl<T> AtomicStack<T> {
+    pub fn new() -> Self {
+        AtomicStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            threads_inside: AtomicUsize::new(0),
+            to_be_deleted: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: ptr::null_mut(),
+            pending_next: ptr::null_mut(),
+        }));
+        self.threads_inside.fetch_add(1, Ordering::SeqCst);
+        let mut old_head = self.head.load(Ordering::SeqCst);
+        loop {
+            // SAFETY: nobody else can see `node` until the CAS below publishes it.
+            unsafe { (*node).next = old_head };
+            // Release would be enough to make the node's contents visible to whoever loads it as
+            // the head, but SeqCst keeps every change of `head` in the single order pop relies on.
+            match self.head.compare_exchange_weak(old_head, node, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(current) => old_head = current,
+            }
+        }
+        self.threads_inside.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    // Every atomic operation on `head` and `threads_inside` in here (and in push and try_reclaim)
+    // is SeqCst, as in the book. Acquire/Release isn't enough: with those, our head load may still
+    // see a node that another thread has already unlinked while that thread's load of threads_inside
+    // doesn't see our fetch_add yet (store buffering), and it frees the node under us. SeqCst
+    // puts all of them in one order that every thread agrees on; see try_reclaim for why that's
+    // enough.
+    pub fn pop(&self) -> Option<T> {
+        self.threads_inside.fetch_add(1, Ordering::SeqCst);
+
+        let mut old_head = self.head.load(Ordering::SeqCst);
+        while !old_head.is_null() {
+            // SAFETY: we were counted in threads_inside before we loaded `old_head` (both SeqCst).
+            // A thread that unlinks it afterwards sees us inside and only queues it on
+            // to_be_deleted; one that unlinked it before, without seeing us, did so before our
+            // load, which then couldn't have returned it. Either way it hasn't been freed.
+            let next = unsafe { (*old_head).next };
+            match self.head.compare_exchange_weak(old_head, next, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(current) => old_head = current,
+            }
+        }
+
+        let elem = if old_head.is_null() {
+            None
+        } else {
+            // SAFETY: our CAS unlinked `old_head`, so we are the only thread that will ever take
+            // its element.
+            Some(unsafe { ManuallyDrop::take(&mut (*old_head).elem) })
+        };
+        self.try_reclaim(old_head);
+        elem
+    }
+
+    // Frees `old_head` (which may be null) and any pending nodes if it is safe, otherwise queues
+    // `old_head` for later. Also takes us back out of threads_inside.
+    fn try_reclaim(&self, old_head: *mut Node<T>) {
+        if self.threads_inside.load(Ordering::SeqCst) == 1 {
+            // We are alone. Claim the pending list; if we are still alone afterwards
+            // nobody can be holding a pointer into it, so it can be freed.
+            let pending = self.to_be_deleted.swap(ptr::null_mut(), Ordering::SeqCst);
+            if self.threads_inside.fetch_sub(1, Ordering::SeqCst) == 1 {
+                // SAFETY: no other thread was inside while we took the list.
+                unsafe { free_chain(pending) };
+            } else if !pending.is_null() {
+                // Someone came in meanwhile and might have read one of these, put them back.
+                self.chain_pending(pending);
+            }
+            // `old_head` is safe to free either way. Nobody older was still inside when we loaded
+            // threads_inside. A newcomer's fetch_add comes after that load in the SeqCst order,
+            // our CAS that unlinked `old_head` comes before it, and the newcomer's head load after
+            // its fetch_add: so the newcomer can only ever load a head from after our CAS, never
+            // `old_head`.
+            if !old_head.is_null() {
+                // SAFETY: see above; its element has already been taken.
+                unsafe { drop(Box::from_raw(old_head)) };
+            }
+        } else {
+            if !old_head.is_null() {
+                self.chain_pending(old_head);
+            }
+            self.threads_inside.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // Pushes a chain of already popped nodes onto the to_be_deleted list.
+    fn chain_pending(&self, first: *mut Node<T>) {
+        // SAFETY: the chain belongs to us, walking it to find the last node is fine.
+        let mut last = first;
+        unsafe {
+            while !(*last).pending_next.is_null() {
+                last = (*last).pending_next;
+            }
+        }
+        let mut current = self.to_be_deleted.load(Ordering::SeqCst);
+        loop {
+            unsafe { (*last).pending_next = current };
+            match self.to_be_deleted.compare_exchange_weak(current, first, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+// Frees a chain of popped nodes. Their elements were already moved out.
+unsafe fn free_chain<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        let boxed = Box::from_raw(node);
+        node = boxed.pending_next;
+    }
+}
+
+impl<T> Default for AtomicStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for AtomicStack<T> {
+    fn drop(&mut self) {
+        // &mut self means no other thread can be using the stack, so plain loads are enough.
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            // SAFETY: every node still on the stack owns its element and is owned by the stack.
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { ManuallyDrop::drop(&mut boxed.elem) };
+            node = boxed.next;
+        }
+        // SAFETY: these nodes were popped, their elements are gone already.
+        unsafe { free_chain(*self.to_be_deleted.get_mut()) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AtomicStack;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let stack = AtomicStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn drop_frees_remaining_elements() {
+        struct DropCounter(Arc<AtomicUsize>);
+        impl Drop for DropCounter {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = AtomicStack::new();
+        for _ in 0..5 {
+            stack.push(DropCounter(drops.clone()));
+        }
+        drop(stack.pop());
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        drop(stack);
+        assert_eq!(drops.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn concurrent_push_pop() {
+        // Miri is a lot slower, so it runs a smaller version of the same test.
+        let (threads, ops) = if cfg!(miri) { (4, 50) } else { (8, 10_000) };
+
+        let stack = Arc::new(AtomicStack::new());
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    // Every thread pushes its own values and pops whatever it finds, adding up
+                    // the values it popped.
+                    let mut popped = Vec::new();
+                    for i in 0..ops {
+                        stack.push(t * ops + i);
+                        if i % 3 != 0 {
+                            if let Some(value) = stack.pop() {
+                                popped.push(value);
+                            }
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut seen: Vec<usize> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        while let Some(value) = stack.pop() {
+            seen.push(value);
+        }
+
+        // Every pushed value came out exactly once:
+        seen.sort_unstable();
+        assert_eq!(seen, (0..threads * ops).collect::<Vec<_>>());
+        assert!(stack.is_empty());
+    }
+}
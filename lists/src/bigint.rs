@@ -0,0 +1,311 @@
+// Big integers on top of our linked list:
+// A BigInt is a sign plus a list of "digits", where every digit is a u32. That means we count in
+// base 2^32 instead of base 10. The head of the list holds the most significant digit, the same
+// way we write numbers on paper, and zero is the empty list (so there are never leading zeros).
+//
+// The arithmetic is the schoolbook kind: add/subtract digit by digit carrying (or borrowing) into
+// the next digit, and multiply every digit of one number by every digit of the other. Carries
+// travel from the least significant digit, which is the far end of our list, so most operations
+// start by reversing the digits into a temporary list whose head is the least significant digit.
+
+use crate::generic_lists::List;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Debug)]
+pub struct BigInt {
+    digits: List<u32>,
+    negative: bool,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt { digits: List::new(), negative: false }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.peek().is_none()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // Builds a BigInt and makes sure zero is never negative.
+    fn from_parts(digits: List<u32>, negative: bool) -> Self {
+        let negative = negative && digits.peek().is_some();
+        BigInt { digits, negative }
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        // Push the low digit first so the high digit ends up at the head:
+        let mut digits = List::new();
+        let (high, low) = ((value >> 32) as u32, value as u32);
+        if value > 0 {
+            digits.push(low);
+        }
+        if high > 0 {
+            digits.push(high);
+        }
+        BigInt::from_parts(digits, false)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = BigInt::from(value.unsigned_abs());
+        BigInt::from_parts(magnitude.digits, value < 0)
+    }
+}
+
+// Reverses the digit order (most significant first <-> least significant first):
+fn reversed(digits: &List<u32>) -> List<u32> {
+    let mut out = List::new();
+    for &digit in digits.iter() {
+        out.push(digit);
+    }
+    out
+}
+
+// Leading zeros can show up after subtracting, pop them off the head:
+fn strip_leading_zeros(digits: &mut List<u32>) {
+    while digits.peek() == Some(&0) {
+        digits.pop();
+    }
+}
+
+// Compares two magnitudes (ignoring the sign). More digits means bigger, otherwise the first
+// differing digit from the head decides.
+fn cmp_magnitude(a: &List<u32>, b: &List<u32>) -> Ordering {
+    let (a_len, b_len) = (a.iter().count(), b.iter().count());
+    a_len.cmp(&b_len).then_with(|| a.iter().cmp(b.iter()))
+}
+
+fn add_magnitude(a: &List<u32>, b: &List<u32>) -> List<u32> {
+    let (a_low, b_low) = (reversed(a), reversed(b));
+    let (mut a_iter, mut b_iter) = (a_low.iter(), b_low.iter());
+    let mut sum = List::new();
+    let mut carry = 0u64;
+    loop {
+        let (x, y) = match (a_iter.next(), b_iter.next()) {
+            (None, None) => break,
+            (x, y) => (*x.unwrap_or(&0) as u64, *y.unwrap_or(&0) as u64),
+        };
+        let total = x + y + carry;
+        sum.push(total as u32); // keep the low 32 bits as this digit ...
+        carry = total >> 32; // ... and carry the rest into the next one
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+// Computes a - b, the caller guarantees a >= b.
+fn sub_magnitude(a: &List<u32>, b: &List<u32>) -> List<u32> {
+    let (a_low, b_low) = (reversed(a), reversed(b));
+    let mut b_iter = b_low.iter();
+    let mut difference = List::new();
+    let mut borrow = 0i64;
+    for &x in a_low.iter() {
+        let y = *b_iter.next().unwrap_or(&0);
+        let mut total = x as i64 - y as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            // Borrow one from the next digit, which is worth 2^32 of this one:
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    strip_leading_zeros(&mut difference);
+    difference
+}
+
+fn mul_magnitude(a: &List<u32>, b: &List<u32>) -> List<u32> {
+    let (a_low, b_low) = (reversed(a), reversed(b));
+    let b_len = b_low.iter().count();
+
+    // Partial products are accumulated in a scratch row indexed by digit position (least
+    // significant first), exactly like the rows of a pen and paper multiplication.
+    let mut row = vec![0u32; a_low.iter().count() + b_len];
+    for (i, &x) in a_low.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b_low.iter().enumerate() {
+            // (2^32 - 1)^2 + 2 * (2^32 - 1) still fits in a u64:
+            let total = row[i + j] as u64 + x as u64 * y as u64 + carry;
+            row[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        row[i + b_len] = carry as u32;
+    }
+
+    let mut product = List::new();
+    for digit in row {
+        product.push(digit);
+    }
+    strip_leading_zeros(&mut product);
+    product
+}
+
+// Divides a magnitude by a small number, returning the quotient and remainder. Division runs from
+// the most significant digit, so here the head-first order is exactly what we want.
+fn divrem_small(a: &List<u32>, divisor: u32) -> (List<u32>, u32) {
+    let mut quotient_low = List::new();
+    let mut rem = 0u64;
+    for &digit in a.iter() {
+        let cur = (rem << 32) | digit as u64;
+        quotient_low.push((cur / divisor as u64) as u32);
+        rem = cur % divisor as u64;
+    }
+    let mut quotient = reversed(&quotient_low);
+    strip_leading_zeros(&mut quotient);
+    (quotient, rem as u32)
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        if self.negative == other.negative {
+            // Same sign: add the magnitudes and keep the sign.
+            return BigInt::from_parts(add_magnitude(&self.digits, &other.digits), self.negative);
+        }
+        // Different signs: subtract the smaller magnitude from the bigger one, the result takes
+        // the sign of the bigger one.
+        match cmp_magnitude(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(sub_magnitude(&other.digits, &self.digits), other.negative)
+            }
+            _ => BigInt::from_parts(sub_magnitude(&self.digits, &other.digits), self.negative),
+        }
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    // a - b is a + (-b):
+    fn sub(self, other: BigInt) -> BigInt {
+        let negated = BigInt::from_parts(other.digits, !other.negative);
+        self + negated
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        BigInt::from_parts(
+            mul_magnitude(&self.digits, &other.digits),
+            self.negative != other.negative,
+        )
+    }
+}
+
+impl PartialEq for BigInt {
+    fn eq(&self, other: &BigInt) -> bool {
+        self.negative == other.negative
+            && cmp_magnitude(&self.digits, &other.digits) == Ordering::Equal
+    }
+}
+
+impl Eq for BigInt {}
+
+// To print in decimal we keep dividing by 10^9 (the biggest power of ten that fits in a u32) and
+// collect the remainders, which are groups of nine decimal digits from the right.
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u32 = 1_000_000_000;
+
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Pushing the remainders means the most significant chunk ends up at the head:
+        let mut chunks = List::new();
+        let (mut rest, rem) = divrem_small(&self.digits, CHUNK);
+        chunks.push(rem);
+        while rest.peek().is_some() {
+            let (quotient, rem) = divrem_small(&rest, CHUNK);
+            chunks.push(rem);
+            rest = quotient;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut iter = chunks.iter();
+        if let Some(first) = iter.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in iter {
+            write!(f, "{:09}", chunk)?; // inner chunks keep their leading zeros
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BigInt;
+
+    #[test]
+    fn two_to_the_64_plus_one() {
+        // u64::MAX + 1 = 2^64 needs a third base 2^32 digit:
+        let two_64 = BigInt::from(u64::MAX) + BigInt::from(1u64);
+        assert_eq!(two_64.to_string(), "18446744073709551616");
+        assert_eq!(two_64.digits, vec![1, 0, 0]);
+
+        let sum = two_64 + BigInt::from(1u64);
+        assert_eq!(sum.to_string(), "18446744073709551617");
+        assert_eq!(sum.digits, vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn subtraction_borrows_across_digits() {
+        // 2^64 - 1 has to borrow through both zero digits:
+        let two_64 = BigInt::from(u64::MAX) + BigInt::from(1u64);
+        let diff = two_64 - BigInt::from(1u64);
+        assert_eq!(diff.digits, vec![u32::MAX, u32::MAX]);
+        assert_eq!(diff, BigInt::from(u64::MAX));
+
+        // Subtracting a bigger number flips the sign:
+        let neg = BigInt::from(5i64) - BigInt::from(8i64);
+        assert!(neg.is_negative());
+        assert_eq!(neg.to_string(), "-3");
+
+        // Cancelling out gives a zero that isn't negative:
+        let zero = BigInt::from(-42i64) - BigInt::from(-42i64);
+        assert!(zero.is_zero());
+        assert!(!zero.is_negative());
+        assert_eq!(zero.to_string(), "0");
+    }
+
+    #[test]
+    fn multiply_ten_digit_numbers() {
+        let product = BigInt::from(1_234_567_890u64) * BigInt::from(9_876_543_210u64);
+        assert_eq!(product.to_string(), "12193263111263526900");
+
+        let product = BigInt::from(-1_234_567_890i64) * BigInt::from(9_876_543_210i64);
+        assert_eq!(product.to_string(), "-12193263111263526900");
+
+        // Multiplying by zero gives zero:
+        assert!((BigInt::from(123u64) * BigInt::zero()).is_zero());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(BigInt::from(0u64).to_string(), "0");
+        assert_eq!(BigInt::from(1_000_000_000u64).to_string(), "1000000000");
+        assert_eq!(BigInt::from(i64::MIN).to_string(), i64::MIN.to_string());
+
+        // (2^64)^2 = 2^128:
+        let two_64 = BigInt::from(u64::MAX) + BigInt::from(1u64);
+        let two_64_again = BigInt::from(u64::MAX) + BigInt::from(1u64);
+        assert_eq!((two_64 * two_64_again).to_string(), "340282366920938463463374607431768211456");
+    }
+}
@@ -0,0 +1,224 @@
+// A multi-producer, single-consumer channel, like std::sync::mpsc, built on our List.
+//
+// The senders and the receiver share one Mutex-protected list of messages. Every Sender is a
+// handle to that shared state through an Arc, so cloning a Sender just makes one more handle, and
+// the state lives until the last handle is gone. There is only one Receiver (it isn't Clone), so
+// only one thread ever takes messages out.
+//
+// A List only adds and removes at the front, which would make the channel a stack: the newest
+// message would come out first. To get first-in first-out order, the receiver doesn't pop the
+// shared list directly. When it runs out of messages it takes the *whole* shared list in one go
+// (leaving an empty one behind for the senders) and pops it onto its own private list, which
+// reverses it: the oldest message ends up in front. Every message is moved twice, so it's still
+// O(1) per message, and the lock is held once per batch instead of once per message.
+//
+// recv() never waits; recv_blocking() waits for a message (on a Condvar) until there is one, or
+// until every Sender is gone and none can arrive any more.
+
+use crate::generic_lists::List;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    // Signalled on every send, and when the last Sender goes away.
+    available: Condvar,
+}
+
+struct State<T> {
+    // Newest message at the front.
+    queue: List<T>,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+impl<T> Shared<T> {
+    // A thread that panicked while holding the lock can't have left the list half changed (List
+    // methods don't panic halfway), so a poisoned lock is still fine to use.
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    // Messages already taken from the shared list, oldest at the front.
+    buffer: List<T>,
+}
+
+// The message could not be sent because the Receiver is gone. Like std's, it hands the message
+// back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending on a channel whose receiver is gone")
+    }
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State { queue: List::new(), senders: 1, receiver_alive: true }),
+        available: Condvar::new(),
+    });
+    (Sender { shared: Arc::clone(&shared) }, Receiver { shared, buffer: List::new() })
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let mut state = self.shared.lock();
+        if !state.receiver_alive {
+            return Err(SendError(msg));
+        }
+        state.queue.push(msg);
+        drop(state);
+        self.shared.available.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Sender { shared: Arc::clone(&self.shared) }
+    }
+}
+
+// The last Sender going away means nothing more can arrive. A receiver waiting in
+// recv_blocking would wait forever, so it gets woken up to notice.
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        let last = state.senders == 0;
+        drop(state);
+        if last {
+            self.shared.available.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    // The oldest message that hasn't been received yet, or None if there is none right now.
+    pub fn recv(&mut self) -> Option<T> {
+        if self.buffer.is_empty() {
+            let mut state = self.shared.lock();
+            refill(&mut self.buffer, &mut state);
+        }
+        self.buffer.pop()
+    }
+
+    // Like recv, but waits for a message. None means the channel is empty and every Sender is
+    // gone, so no message will ever come.
+    pub fn recv_blocking(&mut self) -> Option<T> {
+        if self.buffer.is_empty() {
+            let mut state = self.shared.lock();
+            while state.queue.is_empty() && state.senders > 0 {
+                state = self.shared.available.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            refill(&mut self.buffer, &mut state);
+        }
+        self.buffer.pop()
+    }
+}
+
+// Moves everything sent so far into the receiver's buffer, reversing it into oldest-first order.
+// (Not a method: the lock guard borrows self.shared while this changes self.buffer.)
+fn refill<T>(buffer: &mut List<T>, state: &mut State<T>) {
+    for msg in mem::take(&mut state.queue) {
+        buffer.push(msg);
+    }
+}
+
+// Any further send fails instead of piling up messages nobody will read.
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receiver_alive = false;
+        // Drop the unread messages now, not whenever the last Sender goes.
+        let unread = mem::take(&mut state.queue);
+        drop(state);
+        drop(unread);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{channel, SendError};
+    use std::thread;
+
+    #[test]
+    fn messages_arrive_in_order() {
+        let (tx, mut rx) = channel();
+        assert_eq!(rx.recv(), None);
+        for i in 1..=3 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.recv(), Some(1));
+        // Sent while the receiver holds a partly used batch:
+        tx.send(4).unwrap();
+        assert_eq!(rx.recv(), Some(2));
+        assert_eq!(rx.recv(), Some(3));
+        assert_eq!(rx.recv(), Some(4));
+        assert_eq!(rx.recv(), None);
+    }
+
+    #[test]
+    fn many_senders() {
+        let (tx, mut rx) = channel();
+        let handles: Vec<_> = (0..10)
+            .map(|sender| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..100 {
+                        tx.send((sender, i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(msg) = rx.recv_blocking() {
+            received.push(msg);
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(received.len(), 1000);
+        // Each sender's messages come out in the order it sent them:
+        for sender in 0..10 {
+            let mine: Vec<_> = received.iter().filter(|(s, _)| *s == sender).map(|&(_, i)| i).collect();
+            assert_eq!(mine, (0..100).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn last_sender_wakes_a_waiting_receiver() {
+        let (tx, mut rx) = channel::<i32>();
+        let tx2 = tx.clone();
+        let receiver = thread::spawn(move || rx.recv_blocking());
+        drop(tx);
+        drop(tx2);
+        assert_eq!(receiver.join().unwrap(), None);
+    }
+
+    #[test]
+    fn send_after_the_receiver_is_gone() {
+        let (tx, rx) = channel();
+        tx.send(String::from("read")).unwrap();
+        drop(rx);
+        assert_eq!(tx.send(String::from("lost")), Err(SendError(String::from("lost"))));
+        assert_eq!(SendError(1).to_string(), "sending on a channel whose receiver is gone");
+    }
+}
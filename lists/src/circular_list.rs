@@ -0,0 +1,121 @@
+// A circular list: the elements form a ring, so walking past the last element brings you back
+// to the first one. There is always a "current" element (unless the ring is empty) that we can
+// look at, remove, or move away from.
+//
+// A singly linked ring with Box is not possible (the last node would need to own the first one),
+// so instead the ring is split into two of our generic lists:
+//
+//   ahead:  the current element (at the head) and everything after it, in ring order
+//   behind: the elements we already walked past, most recently passed at the head
+//
+// Advancing pops the head of `ahead` and pushes it onto `behind`. When `ahead` runs dry we have
+// gone all the way around, so we pour `behind` back into `ahead` (which also puts it back in ring
+// order). Every element gets poured at most once per lap, so advancing is O(1) amortized.
+
+use crate::generic_lists::List;
+
+pub struct CircularList<T> {
+    ahead: List<T>,
+    behind: List<T>,
+    len: usize,
+}
+
+impl<T> CircularList<T> {
+    pub fn new() -> Self {
+        CircularList { ahead: List::new(), behind: List::new(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Adds an element at the "end" of the ring, i.e. just before the current element, so it is
+    // the last one we reach when walking around from here.
+    pub fn push_back(&mut self, elem: T) {
+        self.behind.push(elem);
+        self.len += 1;
+    }
+
+    // Looks at the current element.
+    pub fn current(&mut self) -> Option<&T> {
+        self.wrap_around();
+        self.ahead.peek()
+    }
+
+    // Moves the current position `n` elements forward around the ring.
+    pub fn advance(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        // Going around the ring a whole number of times changes nothing:
+        for _ in 0..n % self.len {
+            self.wrap_around();
+            let elem = self.ahead.pop().unwrap();
+            self.behind.push(elem);
+        }
+    }
+
+    // Removes and returns the current element. The element after it becomes the current one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        self.wrap_around();
+        let elem = self.ahead.pop()?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    // If we are at the end of `ahead`, the next element is the oldest one in `behind`.
+    fn wrap_around(&mut self) {
+        if self.ahead.is_empty() {
+            while let Some(elem) = self.behind.pop() {
+                self.ahead.push(elem);
+            }
+        }
+    }
+}
+
+impl<T> Default for CircularList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CircularList;
+
+    #[test]
+    fn basics() {
+        let mut ring = CircularList::new();
+        assert_eq!(ring.current(), None);
+        assert_eq!(ring.remove_current(), None);
+        ring.advance(3); // advancing an empty ring does nothing
+
+        ring.push_back(1); ring.push_back(2); ring.push_back(3);
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.current(), Some(&1));
+
+        // Walking off the end wraps back to the start:
+        ring.advance(2);
+        assert_eq!(ring.current(), Some(&3));
+        ring.advance(1);
+        assert_eq!(ring.current(), Some(&1));
+        ring.advance(7); // two full laps plus one
+        assert_eq!(ring.current(), Some(&2));
+
+        // Removing makes the next element current, pushing adds just before the current one:
+        assert_eq!(ring.remove_current(), Some(2));
+        assert_eq!(ring.current(), Some(&3));
+        ring.push_back(4);
+        assert_eq!(ring.len(), 3);
+        ring.advance(1);
+        assert_eq!(ring.current(), Some(&1));
+        ring.advance(1);
+        assert_eq!(ring.current(), Some(&4));
+        ring.advance(1);
+        assert_eq!(ring.current(), Some(&3));
+    }
+}
@@ -0,0 +1,94 @@
+// A difference list (DList), the functional programmer's trick for cheap appends.
+//
+// Appending to a singly linked list means walking to its end, so building a list by appending
+// one element at a time is O(n^2). A DList doesn't store elements at all. It stores a function
+// that takes "the rest of the list" and puts its own elements in front of it:
+//   [1, 2] is represented by |rest| 1 :: 2 :: rest
+// Appending two DLists is then function composition, `|rest| a(b(rest))`, which is O(1) no matter
+// how many elements they hold. All the real work happens once, in `to_list`, when the composed
+// function is applied to the empty list.
+//
+// The functions are `Fn` (callable any number of times), so a singleton has to clone its element
+// into the list instead of moving it.
+
+use crate::generic_lists::List;
+
+pub struct DList<T>(Box<dyn Fn(List<T>) -> List<T>>);
+
+impl<T: 'static> DList<T> {
+    // The identity function: puts nothing in front of the rest.
+    pub fn empty() -> Self {
+        DList(Box::new(|rest| rest))
+    }
+
+    pub fn singleton(elem: T) -> Self
+    where
+        T: Clone,
+    {
+        DList(Box::new(move |mut rest| {
+            rest.push(elem.clone());
+            rest
+        }))
+    }
+
+    // self's elements followed by other's, in O(1): other goes in front of the rest first, then
+    // self in front of that.
+    pub fn append(self, other: Self) -> Self {
+        let (front, back) = (self.0, other.0);
+        DList(Box::new(move |rest| front(back(rest))))
+    }
+
+    pub fn to_list(self) -> List<T> {
+        (self.0)(List::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DList;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // Counts its clones, so we can see when the DList actually touches the elements.
+    struct Counted {
+        value: usize,
+        clones: Rc<Cell<usize>>,
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.clones.set(self.clones.get() + 1);
+            Counted { value: self.value, clones: self.clones.clone() }
+        }
+    }
+
+    #[test]
+    fn basics() {
+        assert!(DList::<i32>::empty().to_list().is_empty());
+        assert_eq!(DList::singleton(1).to_list(), [1]);
+
+        let list = DList::singleton(1)
+            .append(DList::empty())
+            .append(DList::singleton(2).append(DList::singleton(3)))
+            .to_list();
+        assert_eq!(list, [1, 2, 3]);
+    }
+
+    #[test]
+    fn append_a_thousand_singletons() {
+        let clones = Rc::new(Cell::new(0));
+
+        // Appending at the end, the worst case for a plain linked list:
+        let mut dlist = DList::empty();
+        for value in 0..1000 {
+            dlist = dlist.append(DList::singleton(Counted { value, clones: clones.clone() }));
+        }
+        // Appending only composed functions, not a single element was looked at:
+        assert_eq!(clones.get(), 0);
+
+        let list = dlist.to_list();
+        assert_eq!(clones.get(), 1000);
+        assert_eq!(list.len(), 1000);
+        assert_eq!(list.iter().map(|c| c.value).collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+    }
+}
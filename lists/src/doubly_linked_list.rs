@@ -0,0 +1,261 @@
+// A doubly linked list: every node points at the node before it and the node after it, and the
+// list keeps pointers to both ends. That gives O(1) push and pop at either end, and (the reason
+// this exists) O(1) removal or moving of a node we already have a pointer to, no walking needed.
+//
+// Two-way links mean every node has two "owners", which Box can't express, so the nodes are
+// allocated with Box::into_raw and linked with raw pointers. The list owns all of its nodes and
+// frees them in pop/Drop with Box::from_raw.
+//
+// `push_front`/`push_back` hand back a raw pointer to the new node. Another structure can keep
+// that pointer (the LRU cache keeps them in a HashMap) and later give it back to the unsafe
+// `move_to_front`/`remove_node` methods.
+
+use std::marker::PhantomData;
+use std::ptr;
+
+pub struct DoublyLinkedList<T> {
+    head: *mut DllNode<T>,
+    tail: *mut DllNode<T>,
+    len: usize,
+    // We own values of type T through those raw pointers, tell dropck about it. (It doesn't make
+    // the list covariant: the *mut fields keep DoublyLinkedList<T> invariant in T, see the comment
+    // on Link in fifth.rs. Fine here, since the node pointers handed out are *mut too.)
+    _marker: PhantomData<T>,
+}
+
+pub struct DllNode<T> {
+    elem: T,
+    prev: *mut DllNode<T>,
+    next: *mut DllNode<T>,
+}
+
+impl<T> DllNode<T> {
+    pub fn elem(&self) -> &T {
+        &self.elem
+    }
+
+    pub fn elem_mut(&mut self) -> &mut T {
+        &mut self.elem
+    }
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        DoublyLinkedList { head: ptr::null_mut(), tail: ptr::null_mut(), len: 0, _marker: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) -> *mut DllNode<T> {
+        let node = Box::into_raw(Box::new(DllNode { elem, prev: ptr::null_mut(), next: ptr::null_mut() }));
+        // SAFETY: `node` is fresh and not in any list yet.
+        unsafe { self.link_front(node) };
+        self.len += 1;
+        node
+    }
+
+    pub fn push_back(&mut self, elem: T) -> *mut DllNode<T> {
+        let node = Box::into_raw(Box::new(DllNode { elem, prev: self.tail, next: ptr::null_mut() }));
+        // SAFETY: the tail (if any) is a live node of this list.
+        unsafe {
+            match self.tail.as_mut() {
+                Some(old_tail) => old_tail.next = node,
+                None => self.head = node,
+            }
+        }
+        self.tail = node;
+        self.len += 1;
+        node
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.head.is_null() {
+            return None;
+        }
+        // SAFETY: the head is a live node of this list.
+        Some(unsafe { self.remove_node(self.head) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.tail.is_null() {
+            return None;
+        }
+        // SAFETY: the tail is a live node of this list.
+        Some(unsafe { self.remove_node(self.tail) })
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        // SAFETY: the head is null or a live node owned by this list.
+        unsafe { self.head.as_ref().map(|node| &node.elem) }
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        // SAFETY: the tail is null or a live node owned by this list.
+        unsafe { self.tail.as_ref().map(|node| &node.elem) }
+    }
+
+    /// Unlinks `node` and frees it, returning its element.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of *this* list that hasn't been removed yet.
+    pub unsafe fn remove_node(&mut self, node: *mut DllNode<T>) -> T {
+        self.unlink(node);
+        self.len -= 1;
+        Box::from_raw(node).elem
+    }
+
+    /// Moves `node` to the front of the list in O(1).
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of *this* list that hasn't been removed yet.
+    pub unsafe fn move_to_front(&mut self, node: *mut DllNode<T>) {
+        if self.head != node {
+            self.unlink(node);
+            self.link_front(node);
+        }
+    }
+
+    // Takes `node` out of the chain, pointing its neighbours (or head/tail) at each other.
+    unsafe fn unlink(&mut self, node: *mut DllNode<T>) {
+        let (prev, next) = ((*node).prev, (*node).next);
+        match prev.as_mut() {
+            Some(prev) => prev.next = next,
+            None => self.head = next,
+        }
+        match next.as_mut() {
+            Some(next) => next.prev = prev,
+            None => self.tail = prev,
+        }
+        (*node).prev = ptr::null_mut();
+        (*node).next = ptr::null_mut();
+    }
+
+    // Links an unlinked `node` in as the new head.
+    unsafe fn link_front(&mut self, node: *mut DllNode<T>) {
+        (*node).prev = ptr::null_mut();
+        (*node).next = self.head;
+        match self.head.as_mut() {
+            Some(old_head) => old_head.prev = node,
+            None => self.tail = node,
+        }
+        self.head = node;
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head, _list: PhantomData }
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+// Iter walks the `next` pointers front to back, borrowing the list so it can't change meanwhile.
+pub struct Iter<'a, T> {
+    next: *mut DllNode<T>,
+    _list: PhantomData<&'a DoublyLinkedList<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // SAFETY: the list is borrowed for 'a, so its nodes stay alive and unchanged.
+        unsafe {
+            self.next.as_ref().map(|node| {
+                self.next = node.next;
+                &node.elem
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DoublyLinkedList;
+
+    #[test]
+    fn basics() {
+        let mut list = DoublyLinkedList::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek_front(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&3));
+
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        // Still works after being emptied:
+        list.push_back(4);
+        assert_eq!(list.peek_front(), Some(&4));
+        assert_eq!(list.peek_back(), Some(&4));
+    }
+
+    #[test]
+    fn node_handles() {
+        let mut list = DoublyLinkedList::new();
+        let a = list.push_back('a');
+        let b = list.push_back('b');
+        let c = list.push_back('c');
+
+        unsafe {
+            // Move the tail, then a middle node, then the head (a no-op) to the front:
+            list.move_to_front(c);
+            assert_eq!(list.iter().collect::<String>(), "cab");
+            list.move_to_front(a);
+            assert_eq!(list.iter().collect::<String>(), "acb");
+            list.move_to_front(a);
+            assert_eq!(list.iter().collect::<String>(), "acb");
+            assert_eq!(list.peek_back(), Some(&'b'));
+
+            // Remove from the middle and from the back:
+            assert_eq!(list.remove_node(c), 'c');
+            assert_eq!(list.remove_node(b), 'b');
+            assert_eq!(list.iter().collect::<String>(), "a");
+            assert_eq!(list.peek_back(), Some(&'a'));
+            assert_eq!(list.len(), 1);
+
+            *(*a).elem_mut() = 'z';
+            assert_eq!((*a).elem(), &'z');
+        }
+    }
+
+    #[test]
+    fn drop_non_empty() {
+        use std::rc::Rc;
+
+        let shared = Rc::new(());
+        let mut list = DoublyLinkedList::new();
+        for _ in 0..10 {
+            list.push_back(shared.clone());
+        }
+        assert_eq!(Rc::strong_count(&shared), 11);
+        drop(list);
+        assert_eq!(Rc::strong_count(&shared), 1);
+    }
+}
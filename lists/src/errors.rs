@@ -0,0 +1,30 @@
+// Errors for the fallible ("try_") versions of the list methods.
+//
+// The indexed methods like `insert`, `remove` and `split_off` panic on a bad index, just like the
+// ones on Vec. That's fine inside this crate, but somebody using the list as a library might
+// rather get an error back that they can handle, so every one of them has a `try_` twin that
+// returns a Result<_, ListError> instead.
+
+use core::error::Error;
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListError {
+    // The index was past the end of the list (the list had `len` elements at the time).
+    IndexOutOfBounds { index: usize, len: usize },
+    // The operation needs at least one element, but the list was empty.
+    Empty,
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} is out of bounds for a list of length {}", index, len)
+            }
+            ListError::Empty => write!(f, "the list is empty"),
+        }
+    }
+}
+
+impl Error for ListError {}
@@ -0,0 +1,76 @@
+// A C interface to List<i32>, so C code (or anything else speaking the C ABI) can use the list.
+//
+// C can't know the layout of a Rust List, so it only ever gets an opaque pointer to one: the list
+// is boxed on the Rust side, Box::into_raw hands the heap pointer out as a `void *`, and every
+// function turns it back into a &mut List<i32> for the duration of the call. list_free gives the
+// pointer back to Box::from_raw so Rust drops the list and its nodes.
+//
+// From C the functions look like this:
+//
+//   void *list_new(void);
+//   void  list_push(void *lst, int32_t elem);
+//   bool  list_pop(void *lst, int32_t *out);
+//   void  list_free(void *lst);
+//
+// `#[no_mangle]` keeps the symbol names exactly as written so the C linker can find them. A null
+// list pointer is tolerated everywhere (it behaves like an empty list that ignores pushes), but
+// any other pointer must have come from list_new and not been freed yet.
+
+use crate::generic_lists::List;
+use std::ffi::c_void;
+
+pub type OpaqueList = *mut c_void;
+
+// Turns the opaque pointer back into the list it points to, None for null.
+unsafe fn as_list<'a>(lst: OpaqueList) -> Option<&'a mut List<i32>> {
+    (lst as *mut List<i32>).as_mut()
+}
+
+#[no_mangle]
+pub extern "C" fn list_new() -> OpaqueList {
+    Box::into_raw(Box::new(List::<i32>::new())) as OpaqueList
+}
+
+/// Pushes `elem` onto the front of the list.
+///
+/// # Safety
+///
+/// `lst` must be null or a pointer returned by `list_new` that hasn't been passed to `list_free`.
+#[no_mangle]
+pub unsafe extern "C" fn list_push(lst: OpaqueList, elem: i32) {
+    if let Some(list) = as_list(lst) {
+        list.push(elem);
+    }
+}
+
+/// Pops the front element into `*out` and returns true, or returns false (leaving `*out` alone)
+/// if the list is empty.
+///
+/// # Safety
+///
+/// `lst` as for `list_push`; `out` must be valid for writing an i32 (or null, which discards the
+/// popped element).
+#[no_mangle]
+pub unsafe extern "C" fn list_pop(lst: OpaqueList, out: *mut i32) -> bool {
+    match as_list(lst).and_then(List::pop) {
+        Some(elem) => {
+            if !out.is_null() {
+                out.write(elem);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Frees the list and all of its elements. Null is ignored.
+///
+/// # Safety
+///
+/// `lst` as for `list_push`. It must not be used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn list_free(lst: OpaqueList) {
+    if !lst.is_null() {
+        drop(Box::from_raw(lst as *mut List<i32>));
+    }
+}
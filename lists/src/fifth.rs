@@ -0,0 +1,632 @@
+// Learning linked lists, part five: an unsafe singly linked queue.
+//
+// A singly linked list is a stack when you push and pop at the head. To make it a queue (first
+// in, first out) one end has to take elements and the other has to give them out. Popping has
+// to happen at the head (a node doesn't know the node before it), so pushing goes at the tail,
+// and to do that in O(1) the list keeps a pointer to its last node:
+//
+//   head -> [ 1 ] -> [ 2 ] -> [ 3 ]
+//                              ^
+//   tail ----------------------+
+//
+// The last node is then pointed at twice (by the node before it, or `head`, and by `tail`),
+// and one of the two has to be able to change it: push writes the old last node's `next`
+// through `tail`. That's exactly what safe Rust won't allow with Box (one owner) and won't do
+// cheaply with Rc<RefCell> (see fourth.rs), so this is where raw pointers come in.
+//
+// The chapter starts with `head` as an Option<Box<Node>> and only `tail` raw, but Miri rejects
+// that mix: moving or using the Box asserts it is the only way to reach the node, which
+// invalidates the raw tail pointer made from it earlier. So both ends are raw pointers here: a
+// node is leaked out of its Box as soon as it's made, and only goes back into one (Box::from_raw,
+// to free it) once it has been unlinked and nothing else points at it.
+//
+// The one rule that keeps it all correct: `tail` is None exactly when `head` is None, and
+// otherwise points at the last node of the chain starting at `head`.
+//
+// This exercise comes from https://rust-unofficial.github.io/too-many-lists/fifth.html
+
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    // The number of nodes, so len() doesn't have to walk them.
+    len: usize,
+    // The same marker as generic_lists::List has, for the same reasons: the list owns T's (so
+    // dropck knows dropping it drops them), and it is covariant in T like a Box<T> would be.
+    _marker: PhantomData<T>,
+}
+
+// A raw pointer to a node, or None at the end of the list.
+//
+// Why NonNull and not *mut Node<T>: *mut T is invariant in T, and a type that contains one
+// is too, PhantomData<T> or not (the marker can only add constraints, not lift them). So with
+// *mut links a List<&'static str> couldn't be passed where a List<&'a str> is expected, which
+// works for every std collection. NonNull<T> is a *mut T that is covariant (and never null,
+// hence the Option for the end of the list, which costs no space). See the `shorten` check in
+// the tests.
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, tail: None, len: 0, _marker: PhantomData }
+    }
+
+    // Adds `elem` at the back.
+    pub fn push(&mut self, elem: T) {
+        let new_tail = NonNull::from(Box::leak(Box::new(Node { elem, next: None })));
+        match self.tail {
+            // SAFETY: the tail points at the last node, which the list owns.
+            Some(old_tail) => unsafe { (*old_tail.as_ptr()).next = Some(new_tail) },
+            // Empty list: the new node is the head too.
+            None => self.head = Some(new_tail),
+        }
+        self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    // Takes the element at the front.
+    pub fn pop(&mut self) -> Option<T> {
+        self.head.map(|old_head| {
+            // SAFETY: the head was allocated with Box in push and is owned by the list. Once it's
+            // unlinked below nothing points at it any more, so turning it back into a Box (which
+            // frees it at the end of this closure) is fine.
+            let old_head = unsafe { Box::from_raw(old_head.as_ptr()) };
+            self.head = old_head.next;
+            self.len -= 1;
+            if self.head.is_none() {
+                // That was the last node, and `tail` still points at it. Left like that, the next
+                // push would write into freed memory.
+                self.tail = None;
+            }
+            old_head.elem
+        })
+    }
+
+    // The element at the front, the one pop would take. The reference is made from `head`, and
+    // as long as it lives the borrow on the list stops push and pop from running. That matters
+    // for a one-element queue, where `tail` points at the very same node: push writes that
+    // node's `next` through `tail`, which Miri would count as a write under our shared borrow.
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: the head node is owned by the list, and &self keeps it from being changed.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    // The same, mutably. &mut self makes the returned reference the only way to the element
+    // while it lives: `tail` isn't used until the borrow is over.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in peek, with &mut self keeping everyone else out.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Drops every element and frees every node, one pop at a time (no recursion). pop resets
+    // `tail` along with `head` when the last node goes, so the list is ready to be reused.
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    // Takes the elements out front to back, as an iterator. The list is empty afterwards even if
+    // the iterator isn't used up: dropping the Drain clears whatever is left.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { list: self }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+}
+
+// Every element goes in at the tail, so the iterator's order is kept: O(1) each, no walk to the
+// end.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+// The first element the iterator produces is the first one pop returns.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// A List<T> is the only owner of its nodes and their T's, like a Box<T> or a Vec<T>, so it is
+// Send and Sync under the same conditions. (Raw pointers are neither, so this has to be said.)
+// SAFETY: see generic_lists::List, which has the same ownership; no method shares a node with
+// anything outside the list.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+// Iter and IterMut walk the nodes through the raw links, like the list itself. A raw pointer
+// carries no lifetime, so the PhantomData is what ties the iterator (and the references it hands
+// out) to the borrow of the list: as far as the compiler knows, an Iter<'a, T> holds a &'a T.
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+// With PhantomData<&'a mut T> IterMut is also invariant in T, like &mut T has to be.
+pub struct IterMut<'a, T> {
+    next: Link<T>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+// The raw links make the compiler give up on Send and Sync again. SAFETY: an Iter only reads
+// the elements, like a &T would, and an IterMut hands each one out as a single &mut T, so
+// they're Send and Sync exactly when those references are.
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head, _marker: PhantomData }
+    }
+
+    // &mut self means nothing else touches the list (`tail` included) until the iterator and
+    // every reference it handed out are gone.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { next: self.head, _marker: PhantomData }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            // SAFETY: the nodes belong to the list Iter borrows, and that borrow keeps them alive
+            // and unchanged for 'a.
+            unsafe {
+                self.next = (*node.as_ptr()).next;
+                &(*node.as_ptr()).elem
+            }
+        })
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.next.map(|node| {
+            // SAFETY: the list is mutably borrowed for 'a, so nothing else can reach the nodes.
+            // Each node is visited once, so each element is handed out once. Only the element is
+            // borrowed mutably, never the whole node: `next` is read (through the raw pointer)
+            // before the &mut is made, and the &mut doesn't cover it.
+            unsafe {
+                self.next = (*node.as_ptr()).next;
+                &mut (*node.as_ptr()).elem
+            }
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The nodes are only raw pointers to the compiler, which doesn't free those, so the list has to.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// Drain (Borrows the list mutably and empties it):
+// The &mut borrow is all it needs, every element comes out through pop. Nothing can use the
+// list while the Drain is alive, and it's empty once the Drain is gone.
+pub struct Drain<'a, T> {
+    list: &'a mut List<T>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len(), Some(self.list.len()))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+// Dropped early, e.g. after a `take(2)` or a `break`, it drops the rest of the elements.
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.list.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::cell::Cell;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop(), None);
+
+        // Populate list
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        // Check normal removal: first in, first out
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push(4);
+        list.push(5);
+
+        // Check normal removal
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), None);
+
+        // Check the exhaustion case fixed the pointer right
+        list.push(6);
+        list.push(7);
+
+        // Check normal removal
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), Some(7));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn empty_and_refill() {
+        // Every round empties the queue completely, so a tail left pointing at a freed node
+        // would be written through by the next round's first push (Miri catches that right away,
+        // a normal run would likely see elements go missing).
+        let mut list = List::default();
+        for round in 0..10 {
+            assert!(list.is_empty());
+            for i in 0..round {
+                list.push(i);
+            }
+            for i in 0..round {
+                assert_eq!(list.pop(), Some(i));
+            }
+            assert_eq!(list.pop(), None);
+
+            // And a single element, which is the head and the tail at once:
+            list.push(round * 100);
+            assert!(!list.is_empty());
+            assert_eq!(list.pop(), Some(round * 100));
+        }
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+
+        // With one element, head and tail are the same node:
+        list.push(1);
+        assert_eq!(list.peek(), Some(&1));
+        list.push(2);
+        list.push(3);
+        // Pushing at the back doesn't change the front:
+        assert_eq!(list.peek(), Some(&1));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.peek(), Some(&2));
+
+        // A change through peek_mut is what comes out:
+        if let Some(front) = list.peek_mut() {
+            *front *= 10;
+        }
+        assert_eq!(list.peek(), Some(&20));
+        assert_eq!(list.pop(), Some(20));
+
+        // Also on a single node that push writes through `tail` right afterwards:
+        list.pop();
+        list.push(4);
+        *list.peek_mut().unwrap() += 1;
+        list.push(6);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.peek(), Some(&6));
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        assert_eq!(list.iter().next(), None);
+        assert_eq!(list.iter_mut().next(), None);
+
+        for i in 1..=5 {
+            list.push(i);
+        }
+        assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3, &4, &5]);
+        // Iterating doesn't take anything out:
+        assert_eq!((&list).into_iter().sum::<i32>(), 15);
+        assert_eq!(list.peek(), Some(&1));
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
+        for i in 1..=5 {
+            list.push(i);
+        }
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+        // All the &mut at once, each element exactly once:
+        let all: Vec<&mut i32> = (&mut list).into_iter().collect();
+        assert_eq!(all.len(), 5);
+        for elem in all {
+            *elem += 1;
+        }
+
+        // The changes are what comes out, and the tail still works after iter_mut reached it:
+        list.push(6);
+        for expected in [11, 21, 31, 41, 51, 6] {
+            assert_eq!(list.pop(), Some(expected));
+        }
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn interleaved_operations() {
+        use std::collections::VecDeque;
+
+        // Every operation mixed in one long run against a VecDeque, so that under Miri any
+        // pointer one of them invalidates for another gets used. The sequence is fixed
+        // (xorshift from a constant seed) so failures repeat.
+        let steps = if cfg!(miri) { 300 } else { 5_000 };
+        let mut state = 0x9e37_79b9u32;
+        let mut list = List::new();
+        let mut model = VecDeque::new();
+        for step in 0..steps {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            match state % 6 {
+                0 | 1 => {
+                    list.push(step);
+                    model.push_back(step);
+                }
+                2 => assert_eq!(list.pop(), model.pop_front()),
+                3 => {
+                    if let Some(front) = list.peek_mut() {
+                        *front += 1;
+                    }
+                    if let Some(front) = model.front_mut() {
+                        *front += 1;
+                    }
+                    assert_eq!(list.peek(), model.front());
+                }
+                4 => {
+                    for elem in list.iter_mut() {
+                        *elem += 7;
+                    }
+                    for elem in model.iter_mut() {
+                        *elem += 7;
+                    }
+                }
+                _ => assert!(list.iter().eq(model.iter())),
+            }
+            assert_eq!(list.is_empty(), model.is_empty());
+        }
+        assert!(list.iter().eq(model.iter()));
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        list.extend(0..3);
+        list.push(3);
+        assert_eq!(list.len(), 4);
+        // Down by one with every pop, and to 0 with the last:
+        for expected in (0..4).rev() {
+            list.pop();
+            assert_eq!(list.len(), expected);
+            assert_eq!(list.is_empty(), expected == 0);
+        }
+        list.pop();
+        assert_eq!(list.len(), 0);
+        list.push(9);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.iter().count(), list.len());
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let mut list: List<usize> = (0..n).collect();
+        assert_eq!(list.len(), n);
+        assert_eq!(list.peek(), Some(&0));
+        // Extending a non-empty queue appends behind what's there:
+        list.extend(vec![n, n + 1]);
+        assert_eq!(list.len(), n + 2);
+
+        for expected in 0..n + 2 {
+            assert_eq!(list.pop(), Some(expected));
+        }
+        assert_eq!(list.pop(), None);
+        assert!(None::<i32>.into_iter().collect::<List<_>>().is_empty());
+    }
+
+    struct DropCounter<'a>(&'a Cell<usize>);
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn clear() {
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        list.clear();
+        for _ in 0..5 {
+            list.push(DropCounter(&drops));
+        }
+        list.clear();
+        assert_eq!(drops.get(), 5);
+        assert!(list.is_empty());
+        assert_eq!(list.len(), 0);
+        assert!(list.peek().is_none());
+
+        // Both ends were reset, so pushing starts a fresh list:
+        list.push(DropCounter(&drops));
+        list.push(DropCounter(&drops));
+        assert_eq!(list.len(), 2);
+        drop(list.pop());
+        assert_eq!(drops.get(), 6);
+        drop(list);
+        assert_eq!(drops.get(), 7);
+    }
+
+    #[test]
+    fn drain() {
+        let mut list: List<i32> = (1..=5).collect();
+        let mut drain = list.drain();
+        assert_eq!(drain.len(), 5);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.len(), 3);
+        // Dropped with three left, which go too:
+        drop(drain);
+        assert!(list.is_empty());
+        assert_eq!(list.pop(), None);
+
+        list.extend(6..=8);
+        assert_eq!(list.drain().collect::<Vec<_>>(), [6, 7, 8]);
+        assert!(list.is_empty());
+        assert_eq!(list.drain().next(), None);
+
+        // And the list still works after being drained:
+        list.push(9);
+        list.push(10);
+        assert_eq!(list.peek(), Some(&9));
+        assert_eq!(list.iter().collect::<Vec<_>>(), [&9, &10]);
+    }
+
+    #[test]
+    fn drain_dropped_early_drops_the_rest() {
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for _ in 0..6 {
+            list.push(DropCounter(&drops));
+        }
+        for (i, elem) in list.drain().enumerate() {
+            drop(elem);
+            if i == 1 {
+                break;
+            }
+        }
+        assert_eq!(drops.get(), 6);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn strings() {
+        let mut list = List::new();
+        list.push(String::from("first"));
+        list.push(String::from("second"));
+        assert_eq!(list.pop().as_deref(), Some("first"));
+        list.push(String::from("third"));
+        assert_eq!(list.pop().as_deref(), Some("second"));
+        assert_eq!(list.pop().as_deref(), Some("third"));
+        assert_eq!(list.pop(), None);
+        // Some left in it for Drop (and Miri) to free:
+        list.push(String::from("left over"));
+        list.push(String::from("and another"));
+    }
+
+    #[test]
+    fn elements_are_dropped() {
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for _ in 0..10 {
+            list.push(DropCounter(&drops));
+        }
+        drop(list.pop());
+        assert_eq!(drops.get(), 1);
+        // Dropped non-empty, the other 9 go with it:
+        drop(list);
+        assert_eq!(drops.get(), 10);
+    }
+
+    // Variance: this only has to compile. A queue of &'static str can be used as a queue of
+    // shorter-lived &str, which needs List to be covariant in T.
+    fn shorten<'a>(list: List<&'static str>) -> List<&'a str> {
+        list
+    }
+
+    #[test]
+    fn covariance() {
+        let local = String::from("local");
+        let mut list = shorten(List::new());
+        list.push(&local);
+        assert_eq!(list.pop(), Some("local"));
+    }
+
+    static_assertions::assert_impl_all!(List<String>: Send, Sync);
+    static_assertions::assert_not_impl_any!(List<std::rc::Rc<i32>>: Send, Sync);
+    static_assertions::assert_impl_all!(super::Iter<'static, String>: Send, Sync);
+    static_assertions::assert_impl_all!(super::IterMut<'static, String>: Send, Sync);
+    static_assertions::assert_not_impl_any!(super::Iter<'static, std::cell::Cell<i32>>: Send, Sync);
+
+    #[test]
+    fn drop_long_list() {
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let mut list = List::new();
+        for i in 0..n {
+            list.push(i);
+        }
+        // Dropped with everything still in it: Drop has to free every node, without recursing.
+        drop(list);
+    }
+}
@@ -0,0 +1,138 @@
+// A List with a maximum length that is part of its type.
+//
+// FixedList<T, MAX> is an ordinary linked list underneath (one heap node per element), but it
+// refuses to grow past MAX elements, so code holding a FixedList<Job, 16> knows there can never be
+// more than 16 jobs in it. Only the methods that add elements need to check the limit; everything
+// that removes or reads goes straight to the inner List.
+
+use crate::generic_lists::{Iter, List};
+
+pub struct FixedList<T, const MAX: usize> {
+    inner: List<T>,
+}
+
+impl<T, const MAX: usize> FixedList<T, MAX> {
+    pub fn new() -> Self {
+        FixedList { inner: List::new() }
+    }
+
+    // Pushes onto the front, or hands the element back if MAX elements are already stored.
+    pub fn try_push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
+        }
+        self.inner.push(elem);
+        Ok(())
+    }
+
+    // Like try_push, but a full list is a bug in the caller, so it panics.
+    pub fn push(&mut self, elem: T) {
+        if self.try_push(elem).is_err() {
+            panic!("FixedList is full (capacity {})", MAX);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.inner.pop()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.inner.peek()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.inner.peek_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.inner.len() >= MAX
+    }
+
+    pub fn capacity(&self) -> usize {
+        MAX
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    // Gives up the limit and returns the plain list.
+    pub fn into_list(self) -> List<T> {
+        self.inner
+    }
+}
+
+impl<T, const MAX: usize> Default for FixedList<T, MAX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FixedList;
+
+    #[test]
+    fn push_up_to_capacity() {
+        let mut list: FixedList<i32, 3> = FixedList::new();
+        assert_eq!(list.capacity(), 3);
+        for i in 1..=3 {
+            assert_eq!(list.try_push(i), Ok(()));
+        }
+        assert!(list.is_full());
+
+        // One more doesn't fit, and the element comes back unharmed:
+        assert_eq!(list.try_push(4), Err(4));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek(), Some(&3));
+
+        // Popping makes room again:
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.try_push(4), Ok(()));
+        assert_eq!(list.into_list(), [4, 2, 1]);
+    }
+
+    #[test]
+    fn len_never_exceeds_max() {
+        let mut list: FixedList<u32, 4> = FixedList::default();
+        // A fixed pseudo-random sequence of pushes and pops:
+        let mut state = 12345u32;
+        for _ in 0..200 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            if state.is_multiple_of(3) {
+                list.pop();
+            } else if let Some(top) = list.peek_mut() {
+                *top += 1;
+                let _ = list.try_push(state);
+            } else {
+                list.push(state);
+            }
+            assert!(list.len() <= 4);
+            assert_eq!(list.iter().count(), list.len());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "FixedList is full (capacity 2)")]
+    fn push_past_capacity_panics() {
+        let mut list: FixedList<i32, 2> = FixedList::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut list: FixedList<i32, 0> = FixedList::new();
+        assert!(list.is_full() && list.is_empty());
+        assert_eq!(list.try_push(1), Err(1));
+    }
+}
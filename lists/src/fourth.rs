@@ -0,0 +1,841 @@
+// Learning linked lists, part four: a doubly linked deque in 100% safe Rust.
+//
+// Every node points at the node after it *and* the node before it, and the list points at both
+// ends, so we can push and pop at either end in O(1). That means two owners for every node (its
+// neighbour on each side, or the list itself at the ends), which is what Rc is for. And since we
+// need to change a node's links while others point at it, the node also goes in a RefCell, which
+// moves the borrow checking to runtime:
+//
+//   head -> [ 1 ] <-> [ 2 ] <-> [ 3 ] <- tail
+//
+// The catch: two nodes pointing at each other are an Rc cycle, and an Rc cycle is never freed.
+// Every node is kept alive by its neighbours. So the list has to break the links itself: popping
+// a node detaches it in both directions before handing out its element, and Drop pops everything.
+//
+// The chapter this comes from is called "A Bad Safe Deque" for good reason (the RefCells leak
+// into every API that hands out references, see the later chapters), but it is worth doing once.
+//
+// This exercise comes from https://rust-unofficial.github.io/too-many-lists/fourth.html
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+}
+
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn new(elem: T) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Node { elem, prev: None, next: None }))
+    }
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, tail: None, len: 0 }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let new_head = Node::new(elem);
+        match self.head.take() {
+            Some(old_head) => {
+                // The old head and the new one point at each other, and the new one is the head.
+                old_head.borrow_mut().prev = Some(new_head.clone());
+                new_head.borrow_mut().next = Some(old_head);
+                self.head = Some(new_head);
+            }
+            None => {
+                // Empty list: the one node is both the head and the tail.
+                self.tail = Some(new_head.clone());
+                self.head = Some(new_head);
+            }
+        }
+        self.len += 1;
+    }
+
+    // The mirror image of push_front: swap head/tail and next/prev.
+    pub fn push_back(&mut self, elem: T) {
+        let new_tail = Node::new(elem);
+        match self.tail.take() {
+            Some(old_tail) => {
+                old_tail.borrow_mut().next = Some(new_tail.clone());
+                new_tail.borrow_mut().prev = Some(old_tail);
+                self.tail = Some(new_tail);
+            }
+            None => {
+                self.head = Some(new_tail.clone());
+                self.tail = Some(new_tail);
+            }
+        }
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.take().map(|old_head| {
+            self.len -= 1;
+            match old_head.borrow_mut().next.take() {
+                Some(new_head) => {
+                    // Cut the link back to the old head, or it keeps the old head alive.
+                    new_head.borrow_mut().prev.take();
+                    self.head = Some(new_head);
+                }
+                None => {
+                    // It was the only node, so the tail points at it too.
+                    self.tail.take();
+                }
+            }
+            // Now nothing else points at the node, so we hold the last Rc and can take the node
+            // out of it, and the element out of the RefCell.
+            Rc::try_unwrap(old_head).ok().expect("popped node is still shared").into_inner().elem
+        })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.take().map(|old_tail| {
+            self.len -= 1;
+            match old_tail.borrow_mut().prev.take() {
+                Some(new_tail) => {
+                    new_tail.borrow_mut().next.take();
+                    self.tail = Some(new_tail);
+                }
+                None => {
+                    self.head.take();
+                }
+            }
+            Rc::try_unwrap(old_tail).ok().expect("popped node is still shared").into_inner().elem
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // Removes and drops every element. Dropping the nodes ourselves one at a time matters twice
+    // here: the links between neighbours are Rc cycles that have to be cut by hand, and letting
+    // the compiler drop a node with its `next` still attached would drop the next node inside
+    // that, and so on, one nested call per node. pop_front does both right, it unlinks the head
+    // in both directions and drops only that one node.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // Panics unless the links are consistent: walking forward from `head` along `next` and
+    // backward from `tail` along `prev` both visit exactly `len` nodes, every node's `prev` is
+    // the node the forward walk came from (and its `next` the one the backward walk came from),
+    // and each walk ends at the other end of the list. Either walk gives up after `len` nodes, so
+    // a link that loops back doesn't hang it. Only compiled for tests and debug builds.
+    #[cfg(any(test, debug_assertions))]
+    pub fn debug_validate(&self) {
+        let mut count = 0;
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            assert!(
+                count < self.len,
+                "fourth::List invariant violated: more than {} nodes going forward",
+                self.len
+            );
+            let borrowed = node.borrow();
+            assert!(
+                same_node(&borrowed.prev, &prev),
+                "fourth::List invariant violated: node {}'s prev isn't the node before it",
+                count
+            );
+            count += 1;
+            cur = borrowed.next.clone();
+            drop(borrowed);
+            prev = Some(node);
+        }
+        assert_eq!(count, self.len, "fourth::List invariant violated: wrong node count going forward");
+        assert!(
+            same_node(&prev, &self.tail),
+            "fourth::List invariant violated: walking forward doesn't end at tail"
+        );
+
+        let mut count = 0;
+        let mut next: Link<T> = None;
+        let mut cur = self.tail.clone();
+        while let Some(node) = cur {
+            assert!(
+                count < self.len,
+                "fourth::List invariant violated: more than {} nodes going backward",
+                self.len
+            );
+            let borrowed = node.borrow();
+            assert!(
+                same_node(&borrowed.next, &next),
+                "fourth::List invariant violated: node {} from the back's next isn't the node after it",
+                count
+            );
+            count += 1;
+            cur = borrowed.prev.clone();
+            drop(borrowed);
+            next = Some(node);
+        }
+        assert_eq!(count, self.len, "fourth::List invariant violated: wrong node count going backward");
+        assert!(
+            same_node(&next, &self.head),
+            "fourth::List invariant violated: walking backward doesn't end at head"
+        );
+    }
+
+    // Peeking is where the RefCells leak out. The element is inside a RefCell, and the only way
+    // to look into one is through a guard (Ref) that keeps it borrowed while it lives, so we
+    // can't return a plain &T. Ref::map turns the guard for the whole node into a guard for just
+    // the element, which derefs to &T.
+    //
+    // The guard also keeps &self borrowed, so the borrow checker won't let anyone push or pop
+    // while they hold one:
+    /// ```compile_fail
+    /// let mut list = lists::fourth::List::new();
+    /// list.push_back(1);
+    /// let front = list.peek_front().unwrap();
+    /// list.pop_back();
+    /// assert_eq!(*front, 1);
+    /// ```
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
+        self.head.as_ref().map(|node| Ref::map(node.borrow(), |node| &node.elem))
+    }
+
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
+        self.tail.as_ref().map(|node| Ref::map(node.borrow(), |node| &node.elem))
+    }
+
+    // The same with RefMut: changes made through the guard are made to the element in the list.
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.head.as_ref().map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.tail.as_ref().map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
+    }
+
+    // The elements front to back, moved out of their nodes (so T doesn't have to be Clone).
+    pub fn into_vec(self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len);
+        vec.extend(self);
+        vec
+    }
+}
+
+// Whether two links point at the same node (or are both None).
+#[cfg(any(test, debug_assertions))]
+fn same_node<T>(a: &Link<T>, b: &Link<T>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Walks the list front to back. Each node is borrowed only while its element is formatted and its
+// `next` is read; holding on to the next node takes a clone of its Rc (a count bump, no copy).
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            list.entry(&node.elem);
+            cur = node.next.clone();
+        }
+        list.finish()
+    }
+}
+
+// A new list with clones of the elements, in the same order. Nothing is shared with the original
+// (its nodes have their own neighbours, so they couldn't be).
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut clone = List::new();
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            clone.push_back(node.elem.clone());
+            cur = node.next.clone();
+        }
+        clone
+    }
+}
+
+// The first element the iterator produces ends up at the front.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        for elem in iter {
+            list.push_back(elem);
+        }
+        list
+    }
+}
+
+// The Drop the compiler would generate just drops `head` and `tail`, which only lowers the
+// counts: every node is still held by its neighbours and nothing would be freed. Popping
+// every node breaks all the cycles.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// IntoIter (Consumes the list):
+// Taking elements off either end is exactly what pop_front and pop_back do, so the iterator is
+// just the list itself, and it can be walked from both ends at once (DoubleEndedIterator). The
+// two ends meet when the list is empty, so nothing can come out twice.
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::cell::{Cell, Ref};
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_front(), None);
+
+        // Populate list
+        list.push_front(1);
+        list.push_front(2);
+        list.push_front(3);
+
+        // Check normal removal
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_front(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_front(4);
+        list.push_front(5);
+
+        // Check normal removal
+        assert_eq!(list.pop_front(), Some(5));
+        assert_eq!(list.pop_front(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), None);
+
+        // ---- back -----
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_back(), None);
+
+        // Populate list
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        // Check normal removal
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_back(4);
+        list.push_back(5);
+
+        // Check normal removal
+        assert_eq!(list.pop_back(), Some(5));
+        assert_eq!(list.pop_back(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_back(), None);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert!(list.peek_front().is_none());
+        assert!(list.peek_back().is_none());
+        assert!(list.peek_front_mut().is_none());
+        assert!(list.peek_back_mut().is_none());
+
+        list.push_front(1);
+        list.push_front(2);
+        list.push_front(3);
+        assert_eq!(*list.peek_front().unwrap(), 3);
+        assert_eq!(*list.peek_back().unwrap(), 1);
+        // Two shared guards at once are fine, even on the same node:
+        let (front, again) = (list.peek_front().unwrap(), list.peek_front().unwrap());
+        assert_eq!((*front, *again), (3, 3));
+        drop((front, again));
+
+        *list.peek_front_mut().unwrap() *= 10;
+        *list.peek_back_mut().unwrap() += 100;
+        assert_eq!(*list.peek_front().unwrap(), 30);
+        assert_eq!(*list.peek_back().unwrap(), 101);
+        assert_eq!(list.pop_front(), Some(30));
+        assert_eq!(list.pop_back(), Some(101));
+        assert_eq!(list.pop_back(), Some(2));
+    }
+
+    // With the public API a guard can't outlive a push or pop (see the doc test on peek_front),
+    // so to see what the RefCells do at runtime these hold a borrow of the head node directly,
+    // the way a guard would.
+    #[test]
+    fn borrowed_front_allows_popping_the_back() {
+        let mut list = List::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+        let head = list.head.clone().unwrap();
+        let front = Ref::map(head.borrow(), |node| &node.elem);
+        // pop_back only touches the last two nodes, so it doesn't notice (popping 2 as well
+        // would: node 1 becomes the tail, and its `next` has to be cleared):
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(*front, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn borrowed_front_panics_in_pop_front() {
+        // (One element only: the panic leaves the list half unlinked, and with two nodes their
+        // links to each other would be a cycle that is never freed.)
+        let mut list = List::new();
+        list.push_back(1);
+        let head = list.head.clone().unwrap();
+        let _front = Ref::map(head.borrow(), |node| &node.elem);
+        // pop_front has to borrow the head node mutably to unlink it:
+        list.pop_front();
+    }
+
+    fn one_to_five() -> List<i32> {
+        let mut list = List::new();
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+        list
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut iter = one_to_five().into_iter();
+        for i in 1..=5 {
+            assert_eq!(iter.next(), Some(i));
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let backwards: Vec<i32> = one_to_five().into_iter().rev().collect();
+        assert_eq!(backwards, [5, 4, 3, 2, 1]);
+
+        // Works in a for loop too, through IntoIterator:
+        let mut sum = 0;
+        for elem in one_to_five() {
+            sum += elem;
+        }
+        assert_eq!(sum, 15);
+    }
+
+    #[test]
+    fn into_iter_from_both_ends() {
+        for len in 0..6 {
+            let mut list = List::new();
+            for i in 0..len {
+                list.push_back(i);
+            }
+            let mut iter = list.into_iter();
+            let mut seen = Vec::new();
+            // Alternate between the ends until they meet:
+            loop {
+                let next = if seen.len() % 2 == 0 { iter.next() } else { iter.next_back() };
+                match next {
+                    Some(elem) => seen.push(elem),
+                    None => break,
+                }
+            }
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+            // Every element exactly once:
+            seen.sort_unstable();
+            assert_eq!(seen, (0..len).collect::<Vec<_>>());
+        }
+
+        let mut iter = one_to_five().into_iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(5));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!((list.len(), list.is_empty()), (0, true));
+        let mut expected = 0;
+        for i in 0..20 {
+            match i % 5 {
+                0 | 1 => list.push_front(i),
+                2 | 3 => list.push_back(i),
+                _ => {
+                    assert!(list.pop_front().is_some());
+                    expected -= 2;
+                }
+            }
+            expected += 1;
+            assert_eq!(list.len(), expected);
+        }
+        while list.pop_back().is_some() {
+            expected -= 1;
+            assert_eq!(list.len(), expected);
+        }
+        assert_eq!((list.len(), list.is_empty()), (0, true));
+        // Popping an empty list doesn't go below zero:
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn clear() {
+        let n = if cfg!(miri) { 500 } else { 10_000 };
+        let element = Rc::new(());
+        let mut list = List::new();
+        for i in 0..n {
+            if i % 2 == 0 {
+                list.push_front(element.clone());
+            } else {
+                list.push_back(element.clone());
+            }
+        }
+        // Weak pointers don't keep the nodes alive (a strong one would make popping them fail):
+        let head = Rc::downgrade(list.head.as_ref().unwrap());
+        let tail = Rc::downgrade(list.tail.as_ref().unwrap());
+        assert_eq!(list.len(), n);
+        assert_eq!(Rc::strong_count(&element), n + 1);
+        assert_eq!(head.strong_count(), 2);
+
+        list.clear();
+        assert!(list.is_empty() && list.head.is_none() && list.tail.is_none());
+        // Every element is dropped, and the end nodes are freed too, no cycle kept them alive:
+        assert_eq!(Rc::strong_count(&element), 1);
+        assert!(head.upgrade().is_none() && tail.upgrade().is_none());
+
+        // The list is still usable afterwards:
+        list.push_back(element.clone());
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn debug() {
+        let mut list = List::new();
+        assert_eq!(format!("{:?}", list), "[]");
+        list.push_back("b");
+        list.push_front("a");
+        list.push_back("c");
+        assert_eq!(format!("{:?}", list), r#"["a", "b", "c"]"#);
+        assert_eq!(format!("{:#?}", one_to_five()), "[\n    1,\n    2,\n    3,\n    4,\n    5,\n]");
+    }
+
+    #[test]
+    fn clone() {
+        let original = one_to_five();
+        let counts = |list: &List<i32>| {
+            let mut counts = Vec::new();
+            let mut cur = list.head.clone();
+            while let Some(node) = cur {
+                cur = node.borrow().next.clone();
+                counts.push(Rc::strong_count(&node));
+            }
+            counts
+        };
+        let before = counts(&original);
+
+        let mut clone = original.clone();
+        assert_eq!(format!("{:?}", clone), "[1, 2, 3, 4, 5]");
+        assert_eq!(clone.len(), 5);
+        // No node is shared, so the original's counts are what they were:
+        assert_eq!(counts(&original), before);
+        assert!(!Rc::ptr_eq(original.head.as_ref().unwrap(), clone.head.as_ref().unwrap()));
+
+        // And changing the clone leaves the original alone:
+        *clone.peek_front_mut().unwrap() = 100;
+        clone.pop_back();
+        assert_eq!(format!("{:?}", clone), "[100, 2, 3, 4]");
+        assert_eq!(format!("{:?}", original), "[1, 2, 3, 4, 5]");
+        assert_eq!(format!("{:?}", List::<i32>::new().clone()), "[]");
+    }
+
+    #[test]
+    fn collect_and_into_vec() {
+        let list: List<_> = (1..=5).collect();
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.peek_front().as_deref(), Some(&1));
+        assert_eq!(list.peek_back().as_deref(), Some(&5));
+        assert_eq!(list.into_vec(), [1, 2, 3, 4, 5]);
+
+        let words: List<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(words.into_vec(), ["a", "b"]);
+        assert!(None.into_iter().collect::<List<i32>>().into_vec().is_empty());
+    }
+
+    #[test]
+    fn matches_vec_deque() {
+        use std::collections::VecDeque;
+
+        // Random sequences of operations on both, from a fixed seed so failures can be repeated.
+        let rounds = if cfg!(miri) { 5 } else { 200 };
+        let mut state = 0x2545_f491u32;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for _ in 0..rounds {
+            let mut list = List::new();
+            let mut model = VecDeque::new();
+            for _ in 0..next() % 50 {
+                let value = next();
+                match value % 4 {
+                    0 | 1 => {
+                        list.push_front(value);
+                        model.push_front(value);
+                    }
+                    2 => {
+                        list.push_back(value);
+                        model.push_back(value);
+                    }
+                    _ => assert_eq!(list.pop_back(), model.pop_back()),
+                }
+                assert_eq!(list.len(), model.len());
+            }
+            assert_eq!(list.into_vec(), Vec::from(model));
+        }
+    }
+
+    #[test]
+    fn front_and_back_together() {
+        let mut list = List::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        // 1 2 3
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(1));
+        // 2
+        list.push_front(0);
+        list.push_back(4);
+        // 0 2 4
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_back(), Some(2));
+        // The last node is popped from the other end than it was pushed:
+        assert_eq!(list.pop_back(), Some(0));
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+    }
+
+    #[test]
+    fn popping_detaches_both_directions() {
+        let mut list = List::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+        // The middle node is held by both of its neighbours, the end nodes by one neighbour and
+        // the list (and each by the clone we take here):
+        let first = list.head.clone().unwrap();
+        let middle = first.borrow().next.clone().unwrap();
+        assert_eq!(Rc::strong_count(&middle), 3);
+        assert_eq!(Rc::strong_count(&first), 3);
+        drop(first);
+
+        // After popping the front the middle node has lost its link back:
+        assert_eq!(list.pop_front(), Some(1));
+        assert!(middle.borrow().prev.is_none());
+        // Held by the list's head, by node 3, and by our `middle`:
+        assert_eq!(Rc::strong_count(&middle), 3);
+        assert_eq!(list.pop_back(), Some(3));
+        assert!(middle.borrow().next.is_none());
+        // Now by the head and the tail instead:
+        assert_eq!(Rc::strong_count(&middle), 3);
+        drop(middle);
+        assert_eq!(list.pop_front(), Some(2));
+        assert!(list.head.is_none() && list.tail.is_none());
+    }
+
+    #[test]
+    fn elements_are_freed() {
+        struct DropCounter<'a>(&'a Cell<usize>);
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for _ in 0..5 {
+            list.push_front(DropCounter(&drops));
+            list.push_back(DropCounter(&drops));
+        }
+        drop(list.pop_front());
+        drop(list.pop_back());
+        assert_eq!(drops.get(), 2);
+
+        // Dropping the list frees the other 8, nothing is kept alive by a cycle:
+        drop(list);
+        assert_eq!(drops.get(), 10);
+    }
+
+    // An element that counts how many of its kind are alive. If a node were kept alive by an Rc
+    // cycle, so would be its element, and the count wouldn't go back down. The counter is
+    // thread-local because the tests run on several threads at once.
+    thread_local! {
+        static LIVE: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn live() -> usize {
+        LIVE.with(Cell::get)
+    }
+
+    #[derive(Debug)]
+    struct Instrumented(u32);
+
+    impl Instrumented {
+        fn new(value: u32) -> Self {
+            LIVE.with(|live| live.set(live.get() + 1));
+            Instrumented(value)
+        }
+    }
+
+    impl Drop for Instrumented {
+        fn drop(&mut self) {
+            LIVE.with(|live| live.set(live.get() - 1));
+        }
+    }
+
+    fn instrumented(n: u32) -> List<Instrumented> {
+        let list: List<_> = (0..n).map(Instrumented::new).collect();
+        list.debug_validate();
+        list
+    }
+
+    #[test]
+    fn no_leak_dropping_a_full_list() {
+        let list = instrumented(10);
+        assert_eq!(live(), 10);
+        drop(list);
+        assert_eq!(live(), 0);
+    }
+
+    #[test]
+    fn no_leak_after_partial_pops() {
+        let mut list = instrumented(10);
+        assert_eq!(list.pop_front().map(|e| e.0), Some(0));
+        assert_eq!(list.pop_back().map(|e| e.0), Some(9));
+        list.pop_front();
+        list.debug_validate();
+        assert_eq!(live(), 7);
+        drop(list);
+        assert_eq!(live(), 0);
+
+        // Popped all the way down to one node and back up again:
+        let mut list = instrumented(3);
+        list.pop_back();
+        list.pop_back();
+        list.debug_validate();
+        list.push_front(Instrumented::new(7));
+        list.debug_validate();
+        drop(list);
+        assert_eq!(live(), 0);
+    }
+
+    #[test]
+    fn no_leak_after_peeking() {
+        let mut list = instrumented(4);
+        {
+            let front = list.peek_front().unwrap();
+            assert_eq!(front.0, 0);
+            // The guard only borrows the RefCell, it doesn't hold on to the node's Rc:
+            assert_eq!(Rc::strong_count(list.head.as_ref().unwrap()), 2);
+        }
+        *list.peek_back_mut().unwrap() = Instrumented::new(30);
+        assert_eq!(live(), 4);
+        list.debug_validate();
+        drop(list);
+        assert_eq!(live(), 0);
+    }
+
+    #[test]
+    fn debug_validate() {
+        let mut list = one_to_five();
+        list.debug_validate();
+        List::<i32>::new().debug_validate();
+        list.pop_front();
+        list.push_back(6);
+        list.debug_validate();
+    }
+
+    #[test]
+    fn debug_validate_catches_a_bad_prev() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let list = one_to_five();
+        // Point the third node's prev at the head instead of the second node:
+        let second = list.head.as_ref().unwrap().borrow().next.clone().unwrap();
+        let third = second.borrow().next.clone().unwrap();
+        let old_prev = third.borrow_mut().prev.replace(list.head.clone().unwrap());
+
+        let result = catch_unwind(AssertUnwindSafe(|| list.debug_validate()));
+        let message = result.unwrap_err();
+        assert_eq!(
+            message.downcast_ref::<String>().map(String::as_str),
+            Some("fourth::List invariant violated: node 2's prev isn't the node before it")
+        );
+
+        // Put the link back, or popping the second node finds it still shared (and the head is
+        // held by one Rc too many):
+        third.borrow_mut().prev = old_prev;
+        drop((second, third));
+        list.debug_validate();
+    }
+}
@@ -12,41 +12,212 @@
 // and is intended to help me understand "Advanced Option use", "Generics", "Lifetimes", and
 // "Iterators"
 
+// The list only needs heap allocation, not the rest of std, so everything comes from `alloc`
+// and `core`. That keeps it usable in no_std builds (see lib.rs).
+use crate::errors::ListError;
+use alloc::boxed::Box;
+use alloc::collections::BinaryHeap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::{Ordering, Reverse};
+use core::fmt::{self, Write};
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::ops::{Add, Bound, Index, IndexMut, RangeBounds, Sub};
+use core::ptr::{self, NonNull};
+
 pub struct List<T> { //We can make a list a generic type / set of generic types
     head: Link<T>,
+    // The number of nodes, kept up to date by every method that links or unlinks nodes so len()
+    // doesn't have to walk the list. assert_invariants checks it really matches.
+    len: usize,
+    // The nodes are only reachable through raw pointers now, so as far as the compiler can see
+    // a List doesn't contain any T at all. This marker says it does (it owns them, like a
+    // Box<T> would), which gives us two things:
+    //   - covariance: a List<&'static str> can be used where a List<&'a str> is expected, just
+    //     like with Box. (NonNull is covariant too; a *mut would make the list invariant.)
+    //   - dropck knows that dropping a List drops T's, so it won't let a list outlive
+    //     something its elements borrow.
+    // The marker's type is what decides the variance. With PhantomData<*mut T> the list would
+    // be invariant, and the shortening that works for the real List (see the covariance test)
+    // is rejected. `cargo test` checks that this really doesn't compile:
+    /// ```compile_fail
+    /// use std::marker::PhantomData;
+    ///
+    /// struct InvariantList<T> {
+    ///     _marker: PhantomData<*mut T>,
+    /// }
+    ///
+    /// fn shorten<'a>(list: InvariantList<&'static str>) -> InvariantList<&'a str> {
+    ///     list // error: lifetime may not live long enough
+    /// }
+    /// ```
+    _marker: PhantomData<T>,
 }
 
 
 // So now, Link will be defined as a generic type:
-type Link<T> = Option<Box<Node<T>>>;
+// (It used to be Option<Box<Node<T>>>. The planned tail pointer and cursors need to point at
+// nodes that something else already owns, which Box doesn't allow, so the links are raw
+// NonNull pointers now and the list frees the nodes itself. Every node reachable from `head`
+// was allocated with Box and belongs to exactly one list.)
+type Link<T> = Option<NonNull<Node<T>>>;
 
 struct Node<T> {
     elem: T,
     next: Link<T>
 }
 
+impl<T> Node<T> {
+    // Moves a new node onto the heap and hands out the pointer to it. Whoever links it in owns
+    // it from then on, and has to give it back to Box::from_raw to free it.
+    fn alloc(elem: T, next: Link<T>) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node { elem, next })))
+    }
+}
+
+// Raw pointers are neither Send nor Sync, so the compiler no longer works these out on its own
+// and we have to promise them ourselves (hence `unsafe impl`: the compiler can't check it).
+// They are the same bounds Box<T> and Vec<T> have.
+//
+// SAFETY:
+//   - Send: a List is the only owner of its nodes. No other list, iterator or Rc-like handle
+//     points into them once the borrows of the list are over, and none of the methods keep a
+//     pointer anywhere else (no globals, no thread-locals). So moving the List to another
+//     thread moves all of its nodes and elements with it, and nothing left behind can touch
+//     them. All that is needed is that the elements themselves may move between threads: T: Send.
+//   - Sync: through a &List every method only reads: it follows `next` pointers and hands out
+//     &T, never changes a link or the cached length. The list itself has no interior
+//     mutability (no Cell, no counters updated behind a shared reference), so any number of
+//     threads holding &List at once only ever read the same memory. What they can reach is
+//     &T, which is safe to share exactly when T: Sync. Anything that writes needs &mut List,
+//     which the borrow checker keeps unique across threads too.
+// List<Rc<i32>> is therefore neither Send nor Sync (Rc's count is not atomic), and List<Cell<i32>>
+// is Send but not Sync; the tests check both.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+// Same for a node (Iter and friends hold &Node<T>): it owns the nodes after it, so the same
+// reasoning applies one node further down.
+unsafe impl<T: Send> Send for Node<T> {}
+unsafe impl<T: Sync> Sync for Node<T> {}
+
 
 // The above will not compile because we never use/call any of the elements. We need to implement
 // some code to do this. It is like defining methods for objects
 
 impl<T> List<T> {
     pub fn new() -> Self {
-        List { head: None }
+        List { head: None, len: 0, _marker: PhantomData }
     }
         // This method instantiates a new List with an empty link
         // We refer to variants of an enum using ::, which is the namespacing operator
         // Self is an alias for the type 'List'
         // In Rust, The last expression of a function is implicitly returned. This makes simple functions a little neater. You can still use return to return early like other C-like languages.
     
+    // The arithmetic sequence start, start + step, start + step + step, ... up to but not
+    // including `end`, like (start..end).step_by(step) but for any type that can be added and
+    // compared, floats included. A negative step counts down towards an `end` below `start`.
+    // Panics if the step is zero, since the sequence would never get anywhere. T::default() is
+    // taken as the zero, which it is for all the number types. Also panics on a NaN step, which
+    // is neither above nor below zero, so there'd be no direction to go in.
+    //
+    // Each element is the previous one plus step, so:
+    //   - near the end of an integer type, the element after the last one would overflow. So it
+    //     isn't computed once it's sure to be past `end`: from_range_step(250u8, 255, 3) is
+    //     [250, 253]. That's checked as `end - cur <= step` when cur + step could overflow (it
+    //     can't when cur and step have opposite signs), and the subtraction can't overflow
+    //     there since cur and end are on the same side of zero.
+    //   - a float sequence whose step got too small to change the value stops there.
+    //   - with floats the rounding errors add up: from_range_step(0.0, 1.0, 0.1) ends with
+    //     0.9999999999999999, not 0.9, and so has 11 elements.
+    pub fn from_range_step(start: T, end: T, step: T) -> List<T>
+    where
+        T: Add<Output = T> + Sub<Output = T> + PartialOrd + Default + Copy,
+    {
+        let zero = T::default();
+        let ascending = match step.partial_cmp(&zero) {
+            Some(Ordering::Greater) => true,
+            Some(Ordering::Less) => false,
+            Some(Ordering::Equal) => panic!("from_range_step: step must not be zero"),
+            None => panic!("from_range_step: step must not be NaN"),
+        };
+        let mut list = List::new();
+        let mut tail = &mut list.head;
+        let mut cur = start;
+        while if ascending { cur < end } else { cur > end } {
+            let node = Node::alloc(cur, None);
+            *tail = Some(node);
+            // SAFETY: just allocated and linked in.
+            tail = unsafe { &mut (*node.as_ptr()).next };
+            list.len += 1;
+            let step_is_safe = if ascending { cur < zero } else { cur >= zero };
+            if !step_is_safe && if ascending { end - cur <= step } else { end - cur >= step } {
+                break;
+            }
+            let next = cur + step;
+            if if ascending { next <= cur } else { next >= cur } {
+                break;
+            }
+            cur = next;
+        }
+        list.debug_check();
+        list
+    }
+
+    // A list of clones of a sorted slice's elements, smallest at the head. The order is taken as
+    // it is (nothing is compared, so T doesn't even need Ord), which makes this a plain copy of
+    // the slice in the end; an unsorted slice just gives a list in the same unsorted order.
+    //
+    // It's built by halving, the way you'd build a balanced tree from a sorted array: the middle
+    // element's node is made once everything after it is linked, with the left half in front of
+    // it. In a singly linked list the "root" is only the node in the middle of the chain, so the
+    // result is the same list a loop would give, but the recursion only goes log2(n) calls deep.
+    pub fn from_sorted(slice: &[T]) -> List<T>
+    where
+        T: Clone,
+    {
+        // Prepends the clones of `slice` to `rest`. Going right half, middle, left half means
+        // every element is pushed onto what comes after it. Everything built so far is owned by
+        // `rest`, so if a clone panics it is all freed again.
+        fn prepend_halves<T: Clone>(slice: &[T], mut rest: List<T>) -> List<T> {
+            if slice.is_empty() {
+                return rest;
+            }
+            let mid = slice.len() / 2;
+            rest = prepend_halves(&slice[mid + 1..], rest);
+            rest.push(slice[mid].clone());
+            prepend_halves(&slice[..mid], rest)
+        }
+
+        let list = prepend_halves(slice, List::new());
+        list.debug_check();
+        list
+    }
+
+    // The list of elements f produces from a state, front to back: f(seed) gives the first
+    // element and the state to continue from, f of that state the second, and so on until f
+    // returns None. (stream::Stream::unfold does the same lazily, which allows infinite lists;
+    // this one has to end.)
+    pub fn unfold<S, F: FnMut(S) -> Option<(T, S)>>(seed: S, mut f: F) -> List<T> {
+        // The state is moved into f each time, so between calls it lives in an Option. It's None
+        // only after f has returned None, and from_fn isn't called again after that anyway.
+        let mut state = Some(seed);
+        core::iter::from_fn(|| {
+            let (elem, next) = f(state.take()?)?;
+            state = Some(next);
+            Some(elem)
+        })
+        .collect()
+    }
+
     // We want a new function that will let us push an element to the list
     pub fn push(&mut self, elem: T) {
         //We push nodes to lists so let's instantiate a new node to push:
-        let new_node = Box::new(Node {
-            elem: elem,
-            next: self.head.take() //mem::replace(&mut self.head, None) is such a common idiom, that Option has a method called Take to do it for us.
-        });
+        let new_node = Node::alloc(elem, self.head); // Link is Copy now, so no take() needed
         self.head = Some(new_node);
-        // The replacement in line 53 was temporary because here we redefine self.head as a new node
+        self.len += 1;
     }
 
     // To test `push`, we need a `pop` function:
@@ -54,95 +225,3420 @@ impl<T> List<T> {
         //Option takes care of the case when we try to `pop` an Empty node from the list (because
         //we can't pop anything from an empty list).
         //let result;
-        self.head.take().map(|node| { // The "map" method is an example of a closure, which are anonymous functions that allow us to match values
-                self.head = node.next;
-                node.elem
+        self.pop_node().map(|node| node.elem) // The "map" method is an example of a closure, which are anonymous functions that allow us to match values
+    }
+
+    // Unlinks the head node and hands it back as a Box again, so it is freed when the Box goes.
+    fn pop_node(&mut self) -> Option<Box<Node<T>>> {
+        self.head.map(|node| {
+            // SAFETY: the head came from Node::alloc and this list owns it; after unlinking it
+            // here nothing else points to it.
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            self.head = node.next;
+            self.len -= 1;
+            node
         })
     }
 
+    // Adds `elem` at the end of the list. There's no tail pointer (yet), so this walks the whole
+    // list first: O(n). To add many elements at the back, `extend` walks only once.
+    pub fn push_back(&mut self, elem: T) {
+        *self.tail_link() = Some(Node::alloc(elem, None));
+        self.len += 1;
+        self.debug_check();
+    }
+
     // We want to create a method "Peek" which allows us to reference the head of a list:
     // The trick here is that we want to borrow from the element in the list and not "take" from it.
     // "Option" has this built in as the "as_ref" method
     pub fn peek(&self) -> Option<&T> {
-        self.head.as_ref().map(|node| {
-            &node.elem
-        })   
+        self.head.map(|node| {
+            // SAFETY: the node lives as long as the list, and &self keeps it from being changed.
+            unsafe { &(*node.as_ptr()).elem }
+        })
     }
 
     // Make a mutable version of peek:
     pub fn peek_mut(&mut self) -> Option<&mut T> {
-        self.head.as_mut().map(|node| {
-            &mut node.elem
+        self.head.map(|node| {
+            // SAFETY: as in peek, and &mut self makes this the only reference into the list.
+            unsafe { &mut (*node.as_ptr()).elem }
         })
     }
-}
 
-impl<T> Drop for List<T> {
-    fn drop(&mut self) {
-        let mut cur_link = self.head.take(); //mem::replace(&mut self.head, None);
-        while let Some(mut boxed_node) = cur_link {
-            cur_link = boxed_node.next.take() //mem::replace(&mut boxed_node.next, None);
+    // Sort the list by a key that may be expensive to compute (parsing, allocating, ...).
+    // A plain sort_by_key would call `f` O(n log n) times, so instead we call it exactly once per
+    // node, sort the (key, node) pairs, and then relink the nodes in their new order. The nodes
+    // themselves are moved, never cloned. The sort is stable, just like `sort_by_key`.
+    pub fn sort_by_cached_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        // Nothing is unlinked until all keys are computed and sorted, so if `f` or a comparison
+        // panics the list is still intact.
+        let mut keyed = Vec::with_capacity(self.len);
+        for node in self.nodes() {
+            // SAFETY: the nodes belong to self, which we borrow mutably.
+            keyed.push((f(unsafe { &(*node.as_ptr()).elem }), node));
+        }
+
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // Relink back to front so the smallest key ends up at the head:
+        self.head = None;
+        for (_, node) in keyed.into_iter().rev() {
+            // SAFETY: as above, every node is written exactly once.
+            unsafe { (*node.as_ptr()).next = self.head };
+            self.head = Some(node);
+        }
+        self.debug_check();
+    }
+
+    // Walks the chain and yields a pointer to every node, head first. Reading a node's `next`
+    // happens before the node is handed out, so the caller may relink it.
+    fn nodes(&self) -> impl Iterator<Item = NonNull<Node<T>>> {
+        let mut cur = self.head;
+        core::iter::from_fn(move || {
+            let node = cur?;
+            // SAFETY: the nodes stay allocated while the caller holds on to the list.
+            cur = unsafe { (*node.as_ptr()).next };
+            Some(node)
+        })
+    }
+
+    // Sort the list with a merge sort that only rewires the `next` pointers of the existing boxed
+    // nodes, so no extra heap allocation happens while sorting (unlike collecting into a Vec).
+    //
+    // The algorithm: count the nodes, cut the chain in half, sort each half recursively and then
+    // merge the two sorted halves by repeatedly unlinking the smaller head. Every level of the
+    // recursion touches each node once (O(n)) and halving gives log n levels, so it is
+    // O(n log n) time. The only extra memory is the recursion itself, which is log n frames
+    // deep, so O(log n) stack space. Ties keep their original order (the sort is stable).
+    pub fn sort_in_place(&mut self)
+    where
+        T: Ord,
+    {
+        // While sorting, the chain is taken out of the list. If a comparison panics, the nodes
+        // that were in flight are leaked (no double free, just lost memory) and the list is left
+        // empty but valid.
+        let len = core::mem::replace(&mut self.len, 0);
+        let sorted = merge_sort(self.head.take(), len);
+        self.head = sorted;
+        self.len = len;
+        self.debug_check();
+    }
+
+    // Selection sort, for comparison with sort_in_place: the list is split into a sorted front
+    // and an unsorted rest (at first everything). Each round scans the whole rest for its smallest
+    // element, unlinks that node and links it onto the end of the sorted front, until the rest is
+    // empty. Only pointers are rewired, nothing is allocated or cloned.
+    //
+    // Every round is a full scan of what is left, so that's n + (n - 1) + ... + 1 comparisons:
+    // O(n^2) no matter how the input looks, even already sorted. O(1) extra space. On a tie the
+    // earlier node is picked, so the sort is stable.
+    pub fn selection_sort(&mut self)
+    where
+        T: Ord,
+    {
+        // Like sort_in_place, a panicking comparison leaks the detached nodes and leaves the list
+        // empty.
+        let len = core::mem::replace(&mut self.len, 0);
+        let mut unsorted = self.head.take();
+        let mut sorted_tail = &mut self.head;
+        // SAFETY (for all the derefs): we took the whole chain out of self, so we own every node.
+        while let Some(first) = unsorted {
+            // Find the smallest node and the node before it (None if it is the first one):
+            let (mut min, mut before_min) = (first, None);
+            let mut prev = first;
+            let mut cur = unsafe { (*first.as_ptr()).next };
+            while let Some(node) = cur {
+                if unsafe { (*node.as_ptr()).elem < (*min.as_ptr()).elem } {
+                    min = node;
+                    before_min = Some(prev);
+                }
+                prev = node;
+                cur = unsafe { (*node.as_ptr()).next };
+            }
+
+            // Unlink it from the rest...
+            let after_min = unsafe { (*min.as_ptr()).next };
+            match before_min {
+                None => unsorted = after_min,
+                Some(before) => unsafe { (*before.as_ptr()).next = after_min },
+            }
+            // ...and link it onto the sorted front:
+            *sorted_tail = Some(min);
+            sorted_tail = unsafe { &mut (*min.as_ptr()).next };
+        }
+        *sorted_tail = None;
+        self.len = len;
+        self.debug_check();
+    }
+
+    // Bubble sort, the third sort to compare: walk the list comparing each element with the next
+    // one and swap them if they are out of order. After one pass the largest element has
+    // "bubbled" to the end, after the second the two largest, and so on, so every pass can stop
+    // one node earlier. If a whole pass swaps nothing the list is sorted and we stop.
+    //
+    // Unlike the other two sorts this swaps the elements between the nodes instead of relinking
+    // the nodes. O(n^2) comparisons and swaps in general, but only O(n) (a single pass) for a
+    // list that is already sorted. O(1) extra space, and stable since only strictly greater
+    // elements are swapped.
+    pub fn bubble_sort(&mut self)
+    where
+        T: Ord,
+    {
+        let mut unsorted = self.len;
+        while unsorted > 1 {
+            let mut swapped = false;
+            let mut cur = self.head;
+            for _ in 1..unsorted {
+                // SAFETY: the list has at least `unsorted` nodes, so `a` and the node after it
+                // exist. They are two different nodes, so the two &mut don't overlap.
+                unsafe {
+                    let a = cur.unwrap().as_ptr();
+                    let b = (*a).next.unwrap().as_ptr();
+                    if (*a).elem > (*b).elem {
+                        core::mem::swap(&mut (*a).elem, &mut (*b).elem);
+                        swapped = true;
+                    }
+                    cur = (*a).next;
+                }
+            }
+            if !swapped {
+                break;
+            }
+            unsorted -= 1;
+        }
+    }
+
+    // The length is cached, so this is O(1).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // A structural self-check for debugging the pointer juggling in this file. Walks the whole
+    // list (O(n)) and panics if
+    //   - the chain of nodes loops back on itself. Floyd's "tortoise and hare": one pointer moves
+    //     a node per step, the other one every second step, and they can only ever land on the
+    //     same node if the chain is a loop. (With raw pointer links one wrong assignment is all
+    //     it takes.)
+    //   - the cached length doesn't match the number of nodes.
+    // Only compiled in debug builds, or with the "strict" feature.
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    pub fn assert_invariants(&self) {
+        // SAFETY (for the reads of `next`): all nodes in the chain belong to this list.
+        let next = |node: NonNull<Node<T>>| unsafe { (*node.as_ptr()).next };
+        let mut count = 0;
+        let mut slow = self.head;
+        let mut fast = self.head;
+        while let Some(node) = fast {
+            count += 1;
+            fast = next(node);
+            if count % 2 == 0 {
+                slow = slow.and_then(next);
+                if let (Some(s), Some(f)) = (slow, fast) {
+                    assert!(s != f, "list invariant violated: the nodes form a cycle");
+                }
+            }
+        }
+        assert!(
+            count == self.len,
+            "list invariant violated: cached length is {} but the list has {} nodes",
+            self.len,
+            count
+        );
+    }
+
+    // Every method that links or unlinks nodes ends with this, so a broken list is caught right
+    // where it was broken. It compiles to nothing in release builds. The exceptions are push and
+    // pop: they only ever touch the head, and checking the whole list in them would turn every
+    // loop of pushes or pops (draining, into_iter) into O(n^2) in debug builds.
+    #[inline]
+    fn debug_check(&self) {
+        #[cfg(any(debug_assertions, feature = "strict"))]
+        self.assert_invariants();
+    }
+
+    // Lets the tests break the cached length on purpose, to see assert_invariants catch it.
+    #[cfg(all(test, any(debug_assertions, feature = "strict")))]
+    fn corrupt_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    // Same for a cycle: points the last node back at the head. Such a list must never be
+    // dropped (or walked by anything but assert_invariants), the caller has to leak it.
+    #[cfg(all(test, any(debug_assertions, feature = "strict")))]
+    fn corrupt_into_cycle(&mut self) {
+        *self.tail_link() = self.head;
+    }
+
+    // Walks `index` links down the chain and hands back the link found there, so a node can be
+    // spliced in or out at that position. The caller has already checked that index <= len.
+    fn link_at_mut(&mut self, index: usize) -> &mut Link<T> {
+        let mut cur = &mut self.head;
+        for _ in 0..index {
+            // SAFETY: the node belongs to self, and the returned borrow is tied to &mut self.
+            cur = unsafe { &mut (*cur.unwrap().as_ptr()).next };
+        }
+        cur
+    }
+
+    // Inserts `elem` so it ends up at position `index` (0 is the head), shifting everything after
+    // it one place down the list. Panics if index > len, just like Vec::insert.
+    pub fn insert(&mut self, index: usize, elem: T) {
+        let len = self.len();
+        if index > len {
+            panic!("insertion index (is {}) should be <= len (is {})", index, len);
+        }
+        let link = self.link_at_mut(index);
+        *link = Some(Node::alloc(elem, *link));
+        self.len += 1;
+        self.debug_check();
+    }
+
+    // Removes and returns the element at position `index`. Panics if index >= len.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        if index >= len {
+            panic!("removal index (is {}) should be < len (is {})", index, len);
+        }
+        let link = self.link_at_mut(index);
+        // SAFETY: the node belongs to self and is unlinked right below, so this Box is the only
+        // owner left.
+        let node = unsafe { Box::from_raw(link.unwrap().as_ptr()) };
+        let Node { elem, next } = *node;
+        *link = next;
+        self.len -= 1;
+        self.debug_check();
+        elem
+    }
+
+    // Cuts the list in two: `self` keeps the elements [0, at) and the returned list gets [at, len).
+    // Only one link is rewired, no nodes are copied. Panics if at > len.
+    pub fn split_off(&mut self, at: usize) -> List<T> {
+        let len = self.len();
+        if at > len {
+            panic!("`at` split index (is {}) should be <= len (is {})", at, len);
+        }
+        let back = List { head: self.link_at_mut(at).take(), len: len - at, _marker: PhantomData };
+        self.len = at;
+        self.debug_check();
+        back.debug_check();
+        back
+    }
+
+    // Links all of `other`'s nodes on after the last node of self.
+    fn append_chain(&mut self, other: List<T>) {
+        let other_len = other.len;
+        *self.tail_link() = other.head_into_link();
+        self.len += other_len;
+        self.debug_check();
+    }
+
+    // The first n elements and the rest, as two lists. Like split_off, only one link is cut, but
+    // n past the end isn't an error: you get everything and an empty rest.
+    pub fn take_n(mut self, n: usize) -> (List<T>, List<T>) {
+        let rest = self.split_off(n.min(self.len));
+        (self, rest)
+    }
+
+    // Removes the first n elements (all of them if there are fewer) and drops them, front first.
+    pub fn skip_n(&mut self, n: usize) {
+        for _ in 0..n {
+            if self.pop_node().is_none() {
+                break;
+            }
+        }
+        self.debug_check();
+    }
+
+    // The fallible versions of the indexed methods. Instead of panicking they return a ListError
+    // describing what went wrong: `Empty` when an element was needed but the list has none, and
+    // `IndexOutOfBounds` (carrying the index and the length) otherwise.
+    pub fn try_insert(&mut self, index: usize, elem: T) -> Result<(), ListError> {
+        let len = self.len();
+        if index > len {
+            return Err(ListError::IndexOutOfBounds { index, len });
+        }
+        self.insert(index, elem);
+        Ok(())
+    }
+
+    pub fn try_remove(&mut self, index: usize) -> Result<T, ListError> {
+        self.check_index(index)?;
+        Ok(self.remove(index))
+    }
+
+    pub fn try_split_off(&mut self, at: usize) -> Result<List<T>, ListError> {
+        let len = self.len();
+        if at > len {
+            return Err(ListError::IndexOutOfBounds { index: at, len });
+        }
+        Ok(self.split_off(at))
+    }
+
+    pub fn try_get(&self, index: usize) -> Result<&T, ListError> {
+        self.check_index(index)?;
+        // check_index made sure there are more than `index` elements:
+        Ok(self.get(index).unwrap())
+    }
+
+    // The element at `index` (0 is the head), or None if the list is too short. Like
+    // Vec::get, this is the way to look up an index you aren't sure about; `list[index]` panics
+    // instead. It has to walk `index` nodes, so it's O(index), not O(1) like on a Vec.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.iter_mut().nth(index)
+    }
+
+    // Makes sure `index` points at an existing element.
+    fn check_index(&self, index: usize) -> Result<(), ListError> {
+        let len = self.len();
+        if len == 0 {
+            Err(ListError::Empty)
+        } else if index >= len {
+            Err(ListError::IndexOutOfBounds { index, len })
+        } else {
+            Ok(())
+        }
+    }
+
+    // Hands out mutable references to several different elements at once, e.g. to swap two of
+    // them. Two `get_mut` style calls can't do this because the first &mut borrow of the list
+    // would still be alive during the second. Returns None if any index is out of range or the
+    // same index shows up twice (two &mut to one element is exactly what Rust forbids).
+    //
+    // No unsafe is needed: IterMut already hands out one &mut per node and never the same node
+    // twice, so we walk the chain once and park each wanted reference in its slot.
+    pub fn get_many_mut<const N: usize>(&mut self, indices: [usize; N]) -> Option<[&mut T; N]> {
+        for (i, a) in indices.iter().enumerate() {
+            if indices[i + 1..].contains(a) {
+                return None;
+            }
+        }
+
+        let mut slots: [Option<&mut T>; N] = [(); N].map(|_| None);
+        let mut found = 0;
+        for (i, elem) in self.iter_mut().enumerate() {
+            if found == N {
+                break;
+            }
+            if let Some(slot) = indices.iter().position(|&index| index == i) {
+                slots[slot] = Some(elem);
+                found += 1;
+            }
+        }
+
+        // If we ran off the end before filling every slot, some index was out of range:
+        if found < N {
+            return None;
+        }
+        Some(slots.map(|slot| slot.unwrap()))
+    }
+
+    // Counts the pairs (i, j) with i < j where list[i] > list[j], i.e. how far the list is from
+    // being sorted. Checking every pair is O(n^2); the classic trick is a merge sort that counts
+    // while merging. The list can't be indexed in O(1), so we clone the elements into a Vec and
+    // run the counting merge sort there. The list itself is left untouched.
+    pub fn count_inversions(&self) -> usize
+    where
+        T: Ord + Clone,
+    {
+        let mut elems: Vec<T> = self.iter().cloned().collect();
+        count_inversions_sort(&mut elems)
+    }
+
+    // Keeps only the elements for which `f` returns true, like Vec::retain. Order is preserved.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|elem| f(elem))
+    }
+
+    // Like `retain`, but `f` gets a mutable reference, so it can update an element and decide
+    // whether to keep it in the same pass (Vec::retain_mut).
+    // Removed nodes are unlinked by pointing the previous link at the next node, and are dropped
+    // right away, in head-to-tail order. Links are only touched after `f` returns, so if `f`
+    // panics the list is still a valid chain and nothing leaks.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        let mut cur = &mut self.head;
+        while let Some(node) = *cur {
+            let node = node.as_ptr();
+            // SAFETY: the node belongs to self. `cur` points into the node before it, so the
+            // two borrows don't overlap.
+            if f(unsafe { &mut (*node).elem }) {
+                cur = unsafe { &mut (*node).next };
+            } else {
+                // SAFETY: unlinked right away, so the Box is its only owner.
+                let removed = unsafe { Box::from_raw(node) };
+                *cur = removed.next;
+                self.len -= 1;
+                drop(removed);
+            }
+        }
+        self.debug_check();
+    }
+
+    // Removes every element, handing them out head to tail. See `drain_range`.
+    pub fn drain(&mut self) -> DrainRange<'_, T> {
+        self.drain_range(..)
+    }
+
+    // Removes the elements in the index range and yields them in order, like Vec::drain.
+    // The whole range is cut out of the chain up front (two links are rewired and the list is
+    // closed around the gap), so the list is already in its final state while you iterate, and
+    // if the iterator is dropped early the elements it didn't get to are dropped with it.
+    // Panics if start > end or end > len.
+    pub fn drain_range<R: RangeBounds<usize>>(&mut self, range: R) -> DrainRange<'_, T> {
+        let (start, end) = resolve_range(&range, self.len());
+
+        let mut drained = self.split_off(start);
+        let rest = drained.split_off(end - start);
+        self.append_chain(rest);
+
+        DrainRange { drained, _list: PhantomData }
+    }
+
+    // Takes the chain out of a list, leaving it empty (a List can't be destructured because it
+    // implements Drop).
+    fn head_into_link(mut self) -> Link<T> {
+        self.head.take()
+    }
+
+    // Walks to the end of the chain and returns the empty link after the last node, which is
+    // where new nodes get appended.
+    fn tail_link(&mut self) -> &mut Link<T> {
+        let mut tail = &mut self.head;
+        while let Some(node) = *tail {
+            // SAFETY: the node belongs to self, which we borrow mutably.
+            tail = unsafe { &mut (*node.as_ptr()).next };
+        }
+        tail
+    }
+
+    // Replaces the elements in the index range with the items of `replace_with`, like Vec::splice,
+    // and returns the removed elements as a list. Either side may be empty: an empty range just
+    // inserts, an empty replacement just removes. The removed nodes are moved into the returned
+    // list as one piece, not copied. Panics on the same bad ranges as `drain_range`.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> List<T>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let (start, end) = resolve_range(&range, self.len());
+
+        // Cut out [start, end), keeping what came after it aside:
+        let mut removed = self.split_off(start);
+        let rest = removed.split_off(end - start);
+
+        // Append the replacement where the range was and reattach the rest after it:
+        self.extend(replace_with);
+        self.append_chain(rest);
+        removed
+    }
+
+    // The move-to-front heuristic for self-organizing lists: find the first element matching
+    // `pred`, unlink its node and relink it as the new head, so frequently looked up elements
+    // drift towards the front where they are found quickly. O(n), no allocation (the node itself
+    // moves). Returns whether a match was found; a match at the head is already in place.
+    pub fn move_to_front<F: Fn(&T) -> bool>(&mut self, pred: F) -> bool {
+        if self.peek().is_some_and(&pred) {
+            return true;
+        }
+
+        let mut cur = &mut self.head;
+        // SAFETY (for all the derefs below): the nodes belong to self, which we borrow mutably.
+        while let Some(node) = *cur {
+            if pred(unsafe { &(*node.as_ptr()).elem }) {
+                *cur = unsafe { (*node.as_ptr()).next };
+                unsafe { (*node.as_ptr()).next = self.head };
+                self.head = Some(node);
+                self.debug_check();
+                return true;
+            }
+            cur = unsafe { &mut (*node.as_ptr()).next };
+        }
+        false
+    }
+
+    // The whole list back to back `n` times, like slice::repeat: [1, 2].repeat(3) is
+    // [1, 2, 1, 2, 1, 2]. Each repetition is one forward pass cloning the elements, and collect
+    // appends at the tail so the order is kept.
+    pub fn repeat(&self, n: usize) -> List<T>
+    where
+        T: Clone,
+    {
+        (0..n).flat_map(|_| self.iter().cloned()).collect()
+    }
+
+    // Every pair (a, b) with a from self and b from other, in row-major order: the first element
+    // of self is paired with each element of other, then the second one, and so on. That's
+    // self.len() * other.len() pairs, none if either list is empty.
+    pub fn cartesian_product<U: Clone>(&self, other: &List<U>) -> List<(T, U)>
+    where
+        T: Clone,
+    {
+        self.iter()
+            .flat_map(|a| other.iter().map(move |b| (a.clone(), b.clone())))
+            .collect()
+    }
+
+    // Splits the list into `n` consecutive parts whose lengths differ by at most one, the first
+    // parts getting the extra elements: 7 elements into 3 parts is 3 + 2 + 2. With more parts
+    // than elements the trailing parts are empty. Panics if n is 0.
+    //
+    // We know every part's length up front, so each cut is a walk of exactly that many nodes:
+    // O(len + n) overall.
+    pub fn split_into(self, n: usize) -> Vec<List<T>> {
+        assert!(n > 0, "cannot split a list into 0 parts");
+        let len = self.len();
+        let (base, extra) = (len / n, len % n);
+
+        let mut parts = Vec::with_capacity(n);
+        let mut rest = self;
+        for i in 0..n {
+            let size = if i < extra { base + 1 } else { base };
+            let tail = rest.split_off(size);
+            parts.push(rest);
+            rest = tail;
+        }
+        parts
+    }
+
+    // Pairs up the elements of two lists by position like zip, but keeps going until *both* are
+    // used up: once one side runs out its half of the pair is None. So nothing gets dropped, the
+    // result is as long as the longer list.
+    pub fn zip_longest<U>(self, other: List<U>) -> List<(Option<T>, Option<U>)> {
+        let mut left = self.into_iter();
+        let mut right = other.into_iter();
+        core::iter::from_fn(|| match (left.next(), right.next()) {
+            (None, None) => None,
+            pair => Some(pair),
+        })
+        .collect()
+    }
+
+    // zip_longest, but the shorter side is padded with clones of a default value instead of None,
+    // so the pairs come out as plain (T, U).
+    pub fn zip_longest_with<U: Clone>(self, other: List<U>, default_t: T, default_u: U) -> List<(T, U)>
+    where
+        T: Clone,
+    {
+        self.zip_longest(other)
+            .into_iter()
+            .map(|(t, u)| (t.unwrap_or_else(|| default_t.clone()), u.unwrap_or_else(|| default_u.clone())))
+            .collect()
+    }
+
+    // Alternates between the two lists, self first: [1, 2, 3] and [a, b] give [1, a, 2, b, 3].
+    // Once one list runs out, the rest of the other one is attached as a whole. The nodes are
+    // relinked, nothing is allocated.
+    pub fn interleave(self, other: List<T>) -> List<T> {
+        let len = self.len + other.len;
+        let mut chains = [self.head_into_link(), other.head_into_link()];
+
+        let mut merged = List::new();
+        let mut tail = &mut merged.head;
+        let mut turn = 0;
+        loop {
+            match chains[turn] {
+                // SAFETY: the nodes came out of self and other, and now belong to `merged`.
+                Some(node) => unsafe {
+                    chains[turn] = (*node.as_ptr()).next;
+                    *tail = Some(node);
+                    tail = &mut (*node.as_ptr()).next;
+                    turn = 1 - turn;
+                },
+                None => {
+                    *tail = chains[1 - turn].take();
+                    break;
+                }
+            }
+        }
+        merged.len = len;
+        merged.debug_check();
+        merged
+    }
+
+    // Puts the elements at the 1st, 3rd, 5th, ... position first and the ones at the 2nd, 4th,
+    // ... position after them, each group in its original order: [1, 2, 3, 4, 5, 6] becomes
+    // [1, 3, 5, 2, 4, 6]. (The positions count from 1 here, like in the classic "odd even linked
+    // list" problem; it's about where an element is, not about its value.)
+    // One pass deals the nodes out onto two chains, then the second chain is hung off the end of
+    // the first. O(n), nothing is allocated.
+    pub fn odd_even_separate(&mut self) {
+        let (mut odd, mut even): (Link<T>, Link<T>) = (None, None);
+        let mut odd_tail = &mut odd;
+        let mut even_tail = &mut even;
+        let mut cur = self.head.take();
+        let mut is_odd = true;
+        while let Some(node) = cur {
+            // SAFETY: we took the whole chain out of self, so we own every node.
+            unsafe {
+                cur = (*node.as_ptr()).next;
+                if is_odd {
+                    *odd_tail = Some(node);
+                    odd_tail = &mut (*node.as_ptr()).next;
+                } else {
+                    *even_tail = Some(node);
+                    even_tail = &mut (*node.as_ptr()).next;
+                }
+            }
+            is_odd = !is_odd;
+        }
+        *even_tail = None;
+        *odd_tail = even;
+        self.head = odd;
+        self.debug_check();
+    }
+
+    // Whether the list has n or more elements. Both of these are O(1) shorthands over the cached
+    // length.
+    pub fn has_at_least(&self, n: usize) -> bool {
+        self.len >= n
+    }
+
+    // Compares the lengths of two lists, like `self.len().cmp(&other.len())`.
+    pub fn len_cmp(&self, other: &List<T>) -> Ordering {
+        self.len.cmp(&other.len)
+    }
+
+    // Like fold, but instead of only returning the final state it emits one value per element:
+    // `f` gets the running state and the next element, updates the state and returns what to
+    // emit. Running totals of [1, 2, 3] are [1, 3, 6]. (Iterator::scan is the same idea, except
+    // its closure returns an Option so it can stop early.)
+    pub fn scan<S, B, F: Fn(&mut S, T) -> B>(self, init: S, f: F) -> List<B> {
+        let mut state = init;
+        self.into_iter().map(|elem| f(&mut state, elem)).collect()
+    }
+
+    // A new list with clones of the elements in the index range, like `vec[range].to_vec()`.
+    // Bad ranges panic with the same messages as drain_range.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> List<T>
+    where
+        T: Clone,
+    {
+        let (start, end) = resolve_range(&range, self.len());
+        self.iter().skip(start).take(end - start).cloned().collect()
+    }
+
+    // Copies the elements at src_start..src_end over the ones starting at index `dst`, like
+    // slice::copy_within. The ranges may overlap: the source elements are read into a Vec first,
+    // so they are all copied as they were before any of them got overwritten. Then a single walk
+    // from `dst` writes them back, no nodes are added or removed. Bad source ranges panic like
+    // `slice`, and so does a destination with no room for all of them.
+    pub fn copy_within(&mut self, src_start: usize, src_end: usize, dst: usize)
+    where
+        T: Copy,
+    {
+        let (start, end) = resolve_range(&(src_start..src_end), self.len());
+        let count = end - start;
+        let len = self.len();
+        if dst > len - count {
+            panic!("destination index {} has no room for {} elements in a list of length {}", dst, count, len);
+        }
+        let copied: Vec<T> = self.iter().skip(start).take(count).copied().collect();
+        for (slot, elem) in self.iter_mut().skip(dst).zip(copied) {
+            *slot = elem;
+        }
+    }
+
+    // Every run of `n` neighbouring elements, as its own list of clones, like slice::windows:
+    // windows(2) over [1, 2, 3] gives [1, 2] and [2, 3]. The windows overlap, so a list of m
+    // elements has m - n + 1 of them (none if n > m), and each one costs n clones. Panics if n
+    // is 0.
+    pub fn windows(&self, n: usize) -> impl Iterator<Item = List<T>> + '_
+    where
+        T: Clone,
+    {
+        assert!(n != 0, "window size must not be 0");
+        let count = (self.len + 1).saturating_sub(n);
+        // `start` walks one node ahead per window; each window is read from a copy of it.
+        let mut start = self.iter();
+        (0..count).map(move |_| {
+            let window = Iter { next: start.next }.take(n).cloned().collect();
+            start.next();
+            window
+        })
+    }
+
+    // Puts a clone of `sep` between every two neighbouring elements: [a, b, c] becomes
+    // [a, sep, b, sep, c]. The existing nodes stay where they are, a new node is linked in after
+    // each one that has a successor. Lists with fewer than two elements come back as they are.
+    pub fn intersperse(mut self, sep: T) -> List<T>
+    where
+        T: Clone,
+    {
+        let mut cur = self.head;
+        while let Some(node) = cur {
+            // SAFETY: the node belongs to self, which we own.
+            let node = unsafe { &mut *node.as_ptr() };
+            match node.next {
+                None => break,
+                Some(next) => {
+                    node.next = Some(Node::alloc(sep.clone(), Some(next)));
+                    cur = Some(next);
+                }
+            }
+        }
+        self.len = (2 * self.len).saturating_sub(1);
+        self.debug_check();
+        self
+    }
+
+    // Lookups through Borrow, the trick HashMap::get uses: a List<String> can be searched with a
+    // &str and a List<Vec<u8>> with a &[u8], without building an owned value just to compare.
+    pub fn contains_borrowed<Q: ?Sized + PartialEq>(&self, needle: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.find_borrowed(needle).is_some()
+    }
+
+    // The first element equal to `needle`, see contains_borrowed.
+    pub fn find_borrowed<Q: ?Sized + PartialEq>(&self, needle: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        self.iter().find(|elem| (*elem).borrow() == needle)
+    }
+
+    // Formats every element with Display and glues them together with `separator` in between,
+    // head first, like slice::join: [1, 2, 3].join(", ") is "1, 2, 3".
+    pub fn join(&self, separator: &str) -> String
+    where
+        T: fmt::Display,
+    {
+        let mut joined = String::new();
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                joined.push_str(separator);
+            }
+            // Writing into a String can't fail.
+            write!(joined, "{}", elem).unwrap();
+        }
+        joined
+    }
+
+    // The list as a Graphviz graph, to look at the chain of nodes: paste the output into
+    // `dot -Tpng` or an online viewer. Every node is a box labelled with its element's Debug
+    // output, every `next` pointer an arrow, and a "head" label points at the first node:
+    //
+    //   digraph L {
+    //       head [shape=plaintext];
+    //       n0 [label="1"];
+    //       n1 [label="2"];
+    //       head -> n0;
+    //       n0 -> n1;
+    //   }
+    //
+    // The empty list is just `digraph L { head -> null; }`.
+    pub fn to_dot(&self) -> String
+    where
+        T: fmt::Debug,
+    {
+        if self.is_empty() {
+            return String::from("digraph L { head -> null; }");
+        }
+        // Writing into a String can't fail, hence the unwraps.
+        let mut dot = String::from("digraph L {\n    head [shape=plaintext];\n    node [shape=box];\n");
+        for (i, elem) in self.iter().enumerate() {
+            // The label is a quoted DOT string, so quotes and backslashes in the Debug output
+            // (strings and chars have some) need escaping.
+            let label = format!("{:?}", elem).replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(dot, "    n{} [label=\"{}\"];", i, label).unwrap();
+        }
+        dot.push_str("    head -> n0;\n");
+        for i in 1..self.len {
+            writeln!(dot, "    n{} -> n{};", i - 1, i).unwrap();
         }
+        dot.push('}');
+        dot
+    }
+
+    // Removes every element, dropping them head to tail (see Drop below).
+    pub fn clear(&mut self) {
+        self.clear_with(drop);
+    }
+
+    // Empties the list head to tail, handing each element to `f` instead of just dropping it,
+    // e.g. to close handles in order. If `f` panics, the elements it hasn't seen yet are still
+    // in the list and get dropped with it, nothing leaks.
+    pub fn clear_with<F: FnMut(T)>(&mut self, mut f: F) {
+        while let Some(elem) = self.pop() {
+            f(elem);
+        }
+    }
+
+    // The bytes this list has on the heap: one allocation per node, each holding an element and
+    // a next pointer (plus padding). Only the list's own allocations count, not any heap memory
+    // the elements themselves own (a String's buffer, say).
+    //
+    // Compare it with len * size_of::<T>() to see the per-node overhead: for a List<u8> every
+    // byte of data costs 16 bytes of node on a 64-bit machine.
+    pub fn heap_size(&self) -> usize {
+        self.len() * core::mem::size_of::<Node<T>>()
     }
 }
 
-// Write some tests. (Tests are generally written next to the code they support, but within a new
-// namespace).
-#[cfg(test)]
-mod test {
-    use super::List;
-    
-    #[test]
-    fn basics() {
-        // We want to create a new list:
-        let mut list = List::new();
+// Turns any kind of range (a..b, a..=b, ..b, a.., ..) into a start and an end index, checking
+// them against the length of the list the same way slice indexing does.
+fn resolve_range<R: RangeBounds<usize>>(range: &R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if start > end {
+        panic!("range start index {} is greater than end index {}", start, end);
+    }
+    if end > len {
+        panic!("range end index {} is out of range for a list of length {}", end, len);
+    }
+    (start, end)
+}
 
-        // Test empty list behavior:
-        assert_eq!(list.pop(), None);
+// Iterator returned by `drain` and `drain_range`. It owns the nodes that were cut out of the
+// list, and borrows the list mutably so nobody can touch it until the drain is gone.
+pub struct DrainRange<'a, T> {
+    drained: List<T>,
+    _list: PhantomData<&'a mut List<T>>,
+}
 
-        // We want to use push functionality and test the pop functionality on non None values:
-        list.push(1);
-        list.push(2);
-        list.push(3);
+impl<T> Iterator for DrainRange<'_, T> {
+    type Item = T;
 
-        assert_eq!(list.pop(), Some(3));
-        assert_eq!(list.pop(), Some(2));
+    fn next(&mut self) -> Option<T> {
+        self.drained.pop()
+    }
+}
 
-        // Push a few more things to ensure list is not corrupted:
-        list.push(4);
-        list.push(5);
+// Sorts `elems` and returns how many inversions it had.
+fn count_inversions_sort<T: Ord + Clone>(elems: &mut [T]) -> usize {
+    if elems.len() <= 1 {
+        return 0;
+    }
+    let mid = elems.len() / 2;
+    let mut count = count_inversions_sort(&mut elems[..mid]);
+    count += count_inversions_sort(&mut elems[mid..]);
 
-        assert_eq!(list.pop(), Some(5));
-        assert_eq!(list.pop(), Some(4));
+    // Merge the sorted halves. Whenever an element from the right half goes first, it is smaller
+    // than every element still waiting in the left half, and each of those is one inversion.
+    let left = elems[..mid].to_vec();
+    let right = elems[mid..].to_vec();
+    let (mut i, mut j) = (0, 0);
+    for slot in elems.iter_mut() {
+        if j == right.len() || (i < left.len() && left[i] <= right[j]) {
+            *slot = left[i].clone();
+            i += 1;
+        } else {
+            *slot = right[j].clone();
+            j += 1;
+            count += left.len() - i;
+        }
+    }
+    count
+}
 
-        // Check list exhaustion:
-        assert_eq!(list.pop(), Some(1));
-        assert_eq!(list.pop(), None);
+// Sorts a chain of exactly `len` nodes and returns the new head.
+fn merge_sort<T: Ord>(mut head: Link<T>, len: usize) -> Link<T> {
+    if len <= 1 {
+        return head;
     }
 
-    #[test]
-    fn peek() {
-        //Test to see if peek works
-        let mut list = List::new();
-        
-        // Test that peek with no arguments returns none:
-        assert_eq!(list.peek(), None);
-        assert_eq!(list.peek_mut(), None);
-        
-        // Test that peeking at a list with some elements works as expected:
-        list.push(1); list.push(2); list.push(3);
-        assert_eq!(list.peek(), Some(&3));
-        assert_eq!(list.peek_mut(), Some(&mut 3));
+    // Walk to the middle and cut the chain in two:
+    let mid = len / 2;
+    let mut cur = &mut head;
+    for _ in 0..mid {
+        // SAFETY: the chain has `len` nodes and the caller handed all of them to us.
+        cur = unsafe { &mut (*cur.unwrap().as_ptr()).next };
+    }
+    let right = cur.take();
 
-        // Test that the mutable peek can mutate the return value of peek_mut:
-        list.peek_mut().map(|value| {
-            *value = 42 
-            // The * is shorthand for dereference. 
-            //We are peeking at a value in the list, and dereferencing 
-            //the raw mutable pointer. Ex: peek at 3, dereference it and mutate it to be 42.
-        });
+    merge(merge_sort(head, mid), merge_sort(right, len - mid))
+}
 
-        assert_eq!(list.peek(), Some(&42));
-        assert_eq!(list.pop(), Some(42));
+// Merges two sorted chains by moving nodes onto the tail of the result.
+fn merge<T: Ord>(mut left: Link<T>, mut right: Link<T>) -> Link<T> {
+    let mut merged = None;
+    let mut tail = &mut merged;
+    // SAFETY (for the derefs): both chains were handed to us whole, so we own every node.
+    while let (Some(l), Some(r)) = (left, right) {
+        let take_left = unsafe { (*l.as_ptr()).elem <= (*r.as_ptr()).elem }; // `<=` keeps the sort stable
+        let (src, node) = if take_left { (&mut left, l) } else { (&mut right, r) };
+        *src = unsafe { (*node.as_ptr()).next };
+        *tail = Some(node);
+        tail = unsafe { &mut (*node.as_ptr()).next };
+    }
+    // One side ran out, so the rest of the other side is already sorted:
+    *tail = if left.is_some() { left } else { right };
+    merged
+}
+
+// Merges a list of sorted lists into one sorted list, see List::merge_k_sorted.
+pub fn merge_k_sorted<T: Ord>(lists: List<List<T>>) -> List<T> {
+    List::merge_k_sorted(lists)
+}
+
+impl<T: Ord> List<T> {
+    // Merges any number of sorted lists into one sorted list.
+    // Merging them pairwise would cost O(kn) for k lists, so instead we keep a min-heap holding
+    // the current head node of every list, tagged with the index of the list it came from. Each
+    // step pops the node with the overall smallest element, puts the node after it (the new head
+    // of that list) on the heap and moves the popped node onto the tail of the result. The heap
+    // never holds more than k entries, so this is O(n log k), and the nodes are relinked rather
+    // than cloned or reallocated.
+    pub fn merge_k_sorted<I: IntoIterator<Item = List<T>>>(lists: I) -> List<T> {
+        let mut heap = BinaryHeap::new();
+        let mut len = 0;
+        for (source, list) in lists.into_iter().enumerate() {
+            len += list.len;
+            if let Some(node) = list.head_into_link() {
+                heap.push(HeapEntry { node, source });
+            }
+        }
+
+        let mut merged = List::new();
+        let mut tail = &mut merged.head;
+        while let Some(HeapEntry { node, source }) = heap.pop() {
+            // SAFETY: the nodes came out of the input lists, and now belong to `merged`.
+            if let Some(next) = unsafe { (*node.as_ptr()).next } {
+                heap.push(HeapEntry { node: next, source });
+            }
+            *tail = Some(node);
+            tail = unsafe { &mut (*node.as_ptr()).next };
+        }
+        // The last node still points at whatever followed it in its old list (nothing):
+        *tail = None;
+        merged.len = len;
+        merged.debug_check();
+        merged
+    }
+}
+
+impl<T> List<Option<T>> {
+    // Drops the Nones and unwraps the Somes, keeping the order. Same as
+    // `list.into_iter().flatten().collect()`, but it reads better at the call site. The nodes
+    // can't be reused (a Node<Option<T>> isn't a Node<T>), so every kept element gets a new one.
+    pub fn flatten_options(self) -> List<T> {
+        self.into_iter().flatten().collect()
+    }
+}
+
+impl List<char> {
+    // The characters as a String, head first. Same as `list.into_iter().collect::<String>()`.
+    // Every char takes at least one byte, so reserving len bytes up front saves the first few
+    // reallocations (ASCII text never needs another one).
+    pub fn collect_string(self) -> String {
+        let mut string = String::with_capacity(self.len);
+        for c in self {
+            string.push(c);
+        }
+        string
+    }
+}
+
+// A head node waiting in merge_k_sorted's heap (the rest of its list hangs off node.next).
+// BinaryHeap is a max-heap, so the ordering is reversed to make it a min-heap. The source index
+// breaks ties, so equal elements come out in the order of their lists.
+struct HeapEntry<T> {
+    node: NonNull<Node<T>>,
+    source: usize,
+}
+
+impl<T> HeapEntry<T> {
+    fn elem(&self) -> &T {
+        // SAFETY: a node in the heap is owned by merge_k_sorted until it is linked into the result.
+        unsafe { &(*self.node.as_ptr()).elem }
+    }
+}
+
+impl<T: Ord> Ord for HeapEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Reverse((self.elem(), self.source)).cmp(&Reverse((other.elem(), other.source)))
+    }
+}
+
+impl<T: Ord> PartialOrd for HeapEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> PartialEq for HeapEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<T: Ord> Eq for HeapEntry<T> {}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Drops the nodes one at a time from the head, so the elements are always dropped in head-to-tail
+// order. That's a promise (the drop_order test checks it), code may rely on it.
+// With raw pointer links nothing frees the nodes unless we do, so this is no longer just about
+// avoiding deep recursion. If an element's drop panics, the nodes after it are leaked.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // Not pop_node: the cached length isn't needed here, and shouldn't matter if it's wrong.
+        while let Some(node) = self.head {
+            // SAFETY: every node reachable from head belongs to this list and is freed once.
+            let boxed_node = unsafe { Box::from_raw(node.as_ptr()) };
+            self.head = boxed_node.next;
+        }
+    }
+}
+
+// Iterators (see iterators.rs for the walk-through of how these were built up):
+//
+// IntoIter (Consumes the list):
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
+
+// Iter (Does not consume the list. Instead returns immutable references):
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        // SAFETY: the nodes live as long as the list, and &self keeps them from being changed.
+        Iter { next: self.head.map(|node| unsafe { &*node.as_ptr() }) }
+    }
+
+    // The same iterator as iter(), under a name that says what it's for. This list deliberately
+    // has no eager map / filter / ... returning new Lists: every step would allocate a whole list
+    // just for the next step to read it once. Iterator adapters are lazy, so a chain of them runs
+    // element by element and only the final collect (if any) allocates.
+
+    /// Transforming and filtering, collected into a new list:
+    ///
+    /// ```
+    /// use lists::generic_lists::List;
+    ///
+    /// let list = List::from([1, 2, 3, 4, 5]);
+    /// let odd_squares: List<i32> = list.lazy_chain().map(|x| x * x).filter(|x| x % 2 == 1).collect();
+    /// assert_eq!(odd_squares, [1, 9, 25]);
+    /// ```
+    ///
+    /// Folding straight to a value, with nothing collected at all:
+    ///
+    /// ```
+    /// use lists::generic_lists::List;
+    ///
+    /// let list = List::from([1, 2, 3, 4, 5]);
+    /// let sum_of_evens: i32 = list.lazy_chain().filter(|&&x| x % 2 == 0).sum();
+    /// assert_eq!(sum_of_evens, 6);
+    /// assert_eq!(list.lazy_chain().filter(|&&x| x > 2).count(), 3);
+    /// ```
+    ///
+    /// Searching, which stops at the first match:
+    ///
+    /// ```
+    /// use lists::generic_lists::List;
+    ///
+    /// let list = List::from(["apple", "banana", "cherry"]);
+    /// assert_eq!(list.lazy_chain().find(|s| s.starts_with('b')), Some(&"banana"));
+    /// assert_eq!(list.lazy_chain().position(|s| s.len() == 6), Some(1));
+    /// assert!(list.lazy_chain().any(|s| s.contains("err")));
+    /// ```
+    ///
+    /// Taking a few and pairing them up, into a Vec or any other collection:
+    ///
+    /// ```
+    /// use lists::generic_lists::List;
+    ///
+    /// let list = List::from(['a', 'b', 'c', 'd']);
+    /// let numbered: Vec<(usize, char)> = list.lazy_chain().copied().enumerate().skip(1).take(2).collect();
+    /// assert_eq!(numbered, [(1, 'b'), (2, 'c')]);
+    /// ```
+    pub fn lazy_chain(&self) -> Iter<'_, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            // SAFETY: as in List::iter.
+            self.next = node.next.map(|next| unsafe { &*next.as_ptr() });
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> Iter<'a, T> {
+    // Every n-th element, starting with the first: step_by(2) over [1, 2, 3, 4, 5] gives 1, 3, 5.
+    // Panics if n is 0. (This takes priority over Iterator::step_by, which behaves the same.)
+    pub fn step_by(self, n: usize) -> StepBy<Iter<'a, T>> {
+        StepBy::new(self, n)
+    }
+
+    // Repeats the remaining elements forever: after the last one it jumps back to where this
+    // iterator started. Iterator::cycle would need Iter to be Clone; we only need to remember
+    // the starting node.
+    pub fn cycle(self) -> Cycle<'a, T> {
+        Cycle { start: self.next, next: self.next }
+    }
+}
+
+// Only shared references to nodes inside, so Cycle is Send exactly when &T is, i.e. when
+// T: Sync (the compiler works that out for us).
+pub struct Cycle<'a, T> {
+    start: Option<&'a Node<T>>,
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Cycle<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // Back to the start when we ran off the end (an empty list stays empty):
+        let node = self.next.or(self.start)?;
+        // SAFETY: as in List::iter.
+        self.next = node.next.map(|next| unsafe { &*next.as_ptr() });
+        Some(&node.elem)
+    }
+}
+
+// Yields the first item of the inner iterator, then skips n - 1 items before each following one.
+pub struct StepBy<I: Iterator> {
+    iter: I,
+    skip: usize,
+    first: bool,
+}
+
+impl<I: Iterator> StepBy<I> {
+    fn new(iter: I, n: usize) -> Self {
+        assert!(n != 0, "step_by step must not be 0");
+        StepBy { iter, skip: n - 1, first: true }
+    }
+}
+
+impl<I: Iterator> Iterator for StepBy<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if !self.first {
+            for _ in 0..self.skip {
+                self.iter.next()?;
+            }
+        }
+        self.first = false;
+        self.iter.next()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+// ListRef (A read-only view of a list from some node on, like a &List that starts later):
+// The persistent list in third.rs can hand out its tail as a real List in O(1), because the nodes
+// are shared through Rc. Here every node has exactly one owner, and a List is its head pointer
+// *and* its length, so there's no List sitting at the second node that we could return a
+// &List to. What we can return is this: a borrowed pointer to the node plus the number of nodes
+// from there, which is everything a read-only List needs. borrowed_tail is `cdr` without copying.
+pub struct ListRef<'a, T> {
+    head: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    // The list without its first element, as a view borrowing this one. O(1). The tail of an
+    // empty list is empty.
+    pub fn borrowed_tail(&self) -> ListRef<'_, T> {
+        self.as_list_ref().borrowed_tail()
+    }
+
+    // The whole list as a ListRef.
+    pub fn as_list_ref(&self) -> ListRef<'_, T> {
+        // SAFETY: as in List::iter.
+        ListRef { head: self.head.map(|node| unsafe { &*node.as_ptr() }), len: self.len }
+    }
+}
+
+impl<'a, T> ListRef<'a, T> {
+    pub fn head(&self) -> Option<&'a T> {
+        self.head.map(|node| &node.elem)
+    }
+
+    // One node further on, again in O(1). It takes `&self` but the result borrows the list, not
+    // this view, so tails of tails can be taken without keeping the views in between around.
+    pub fn borrowed_tail(&self) -> ListRef<'a, T> {
+        match self.head {
+            // SAFETY: as in List::iter.
+            Some(node) => {
+                ListRef { head: node.next.map(|next| unsafe { &*next.as_ptr() }), len: self.len - 1 }
+            }
+            None => ListRef { head: None, len: 0 },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter { next: self.head }
+    }
+}
+
+// A view is only a reference and a number, so copying it is free (whatever T is, hence not derived).
+impl<T> Clone for ListRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ListRef<'_, T> {}
+
+impl<'a, T> IntoIterator for ListRef<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ListRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// IterMut (Acts like `Iter`, but hands out mutable references):
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+}
+
+impl<T> List<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        // SAFETY: &mut self makes these the only references into the list, and every node is
+        // handed out once.
+        IterMut { next: self.head.map(|node| unsafe { &mut *node.as_ptr() }) }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            // SAFETY: as in List::iter_mut.
+            self.next = node.next.map(|next| unsafe { &mut *next.as_ptr() });
+            &mut node.elem
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+// Cursor (A position in the list that can move forward and edit in place):
+// The cursor sits on a node, or past the last node ("at the end"). It keeps a pointer to the
+// link that points at its node (the list's head, or the previous node's `next`) rather than to
+// the node itself: with only `next` pointers there would be no way back to that link, and it is
+// the link that has to change to insert before the node or remove it. That also makes it a
+// forward-only cursor, the list is singly linked.
+//
+// Both pointers are raw and come from the one `&mut List` the cursor was made from (the
+// PhantomData keeps that borrow alive), so they don't get in each other's way when the cursor
+// changes a link and the cached length of the same list.
+pub struct Cursor<'a, T> {
+    list: NonNull<List<T>>,
+    link: NonNull<Link<T>>,
+    index: usize,
+    _marker: PhantomData<&'a mut List<T>>,
+}
+
+impl<T> List<T> {
+    // A cursor on the head (or at the end, for an empty list).
+    pub fn cursor_mut(&mut self) -> Cursor<'_, T> {
+        let list = NonNull::from(self);
+        // SAFETY: `list` came from a &mut, so it is valid to get the head's address from.
+        let link = unsafe { NonNull::new_unchecked(ptr::addr_of_mut!((*list.as_ptr()).head)) };
+        Cursor { list, link, index: 0, _marker: PhantomData }
+    }
+}
+
+// SAFETY for everything below: `list` is borrowed mutably for 'a, so the cursor has the list to
+// itself, and `link` always points at a link inside it: the head, or the `next` of one of its
+// nodes, which stays allocated as long as it is in the list.
+impl<'a, T> Cursor<'a, T> {
+    // The position: the index of the current element, or the length at the end.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn current_node(&self) -> Option<NonNull<Node<T>>> {
+        unsafe { *self.link.as_ptr() }
+    }
+
+    fn len_mut(&mut self) -> &mut usize {
+        unsafe { &mut (*self.list.as_ptr()).len }
+    }
+
+    // The element under the cursor, None at the end.
+    pub fn current(&self) -> Option<&T> {
+        self.current_node().map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        self.current_node().map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    // Steps to the next element. Returns false, without moving, at the end.
+    pub fn move_next(&mut self) -> bool {
+        match self.current_node() {
+            Some(node) => {
+                self.link = unsafe { NonNull::new_unchecked(ptr::addr_of_mut!((*node.as_ptr()).next)) };
+                self.index += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // The single-element edits only touch the links around the cursor, so like push and pop they
+    // don't run debug_check (which walks the whole list).
+
+    // Inserts `elem` in front of the cursor, which stays on the same element (or at the end).
+    pub fn insert_before(&mut self, elem: T) {
+        let node = Node::alloc(elem, self.current_node());
+        unsafe {
+            *self.link.as_ptr() = Some(node);
+            self.link = NonNull::new_unchecked(ptr::addr_of_mut!((*node.as_ptr()).next));
+        }
+        self.index += 1;
+        *self.len_mut() += 1;
+    }
+
+    // Inserts `elem` right after the current element, the cursor doesn't move. At the end there
+    // is no current element, and the new one is appended (in front of the cursor, so the cursor
+    // stays at the end).
+    pub fn insert_after(&mut self, elem: T) {
+        match self.current_node() {
+            Some(node) => unsafe {
+                let next = &mut (*node.as_ptr()).next;
+                *next = Some(Node::alloc(elem, *next));
+                *self.len_mut() += 1;
+            },
+            None => self.insert_before(elem),
+        }
+    }
+
+    // Unlinks the current element and returns it. The cursor moves on to the element after it.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current_node()?;
+        // SAFETY: the node is unlinked right here, so nothing points at it any more.
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        unsafe { *self.link.as_ptr() = node.next };
+        *self.len_mut() -= 1;
+        Some(node.elem)
+    }
+
+    // Moves all of `other` into the list in front of the cursor, in order. The cursor stays on
+    // the same element, whose index goes up by other's length. Only two links change (plus a walk
+    // over `other` to find its last node), none of the nodes are copied.
+    pub fn splice_before(&mut self, other: List<T>) {
+        let other_len = other.len;
+        let Some(first) = other.head_into_link() else { return };
+        unsafe {
+            let last_next = chain_end(first);
+            *last_next.as_ptr() = *self.link.as_ptr();
+            *self.link.as_ptr() = Some(first);
+            self.link = last_next;
+        }
+        self.index += other_len;
+        *self.len_mut() += other_len;
+        unsafe { (*self.list.as_ptr()).debug_check() };
+    }
+
+    // Moves all of `other` into the list right after the current element, in order. The cursor
+    // stays where it is. At the end this is the same as splice_before: `other` is appended.
+    pub fn splice_after(&mut self, other: List<T>) {
+        let Some(node) = self.current_node() else { return self.splice_before(other) };
+        let other_len = other.len;
+        let Some(first) = other.head_into_link() else { return };
+        unsafe {
+            let next = ptr::addr_of_mut!((*node.as_ptr()).next);
+            *chain_end(first).as_ptr() = *next;
+            *next = Some(first);
+        }
+        *self.len_mut() += other_len;
+        unsafe { (*self.list.as_ptr()).debug_check() };
+    }
+}
+
+// The `next` link of the last node of a chain.
+// SAFETY: `first` must start a valid, finite chain that we own.
+unsafe fn chain_end<T>(first: NonNull<Node<T>>) -> NonNull<Link<T>> {
+    let mut node = first;
+    while let Some(next) = (*node.as_ptr()).next {
+        node = next;
+    }
+    NonNull::new_unchecked(ptr::addr_of_mut!((*node.as_ptr()).next))
+}
+
+// Building a list from an iterator keeps the iterator's order: the first item becomes the head.
+// (Pushing each item would reverse them, so instead we keep a cursor on the last link and fill
+// it in, which is what `extend` does.)
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// Appends the items at the end of the list, in order. Finding the end is an O(n) walk, after that
+// every item is O(1).
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // Walk to the end through `self.head` directly (not tail_link) so the borrow is of the
+        // head field only, and the length can be bumped as each node goes in. Then the count
+        // stays right even if the iterator panics halfway.
+        let mut tail = &mut self.head;
+        while let Some(node) = *tail {
+            // SAFETY: the node belongs to self, which we borrow mutably.
+            tail = unsafe { &mut (*node.as_ptr()).next };
+        }
+        for elem in iter {
+            let node = Node::alloc(elem, None);
+            *tail = Some(node);
+            // SAFETY: just allocated and linked in.
+            tail = unsafe { &mut (*node.as_ptr()).next };
+            self.len += 1;
+        }
+        self.debug_check();
+    }
+}
+
+// For Copy elements we can also extend from references (`list.extend(slice.iter())`), copying
+// each one, like Vec does.
+impl<'a, T: Copy + 'a> Extend<&'a T> for List<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+// List::from([1, 2, 3]) has the elements in array order, 1 at the head.
+impl<T, const N: usize> From<[T; N]> for List<T> {
+    fn from(array: [T; N]) -> Self {
+        // IntoIterator::into_iter explicitly, `array.into_iter()` would iterate by reference in
+        // the 2018 edition.
+        IntoIterator::into_iter(array).collect()
+    }
+}
+
+// list[index], panicking with the same message as a Vec when the index is out of bounds. Use
+// get or get_mut if that's a possibility.
+impl<T> Index<usize> for List<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, index))
+    }
+}
+
+impl<T> IndexMut<usize> for List<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, index))
+    }
+}
+
+// Print the list like a Vec, head first: [3, 2, 1]
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// A List<char> can be written to like a String: `write!(list, "hello {}", name)` adds the
+// characters at the back, in order. write! calls write_str once per piece of the format string, so
+// each piece goes in with a single extend (one walk to the end) rather than a push_back per char.
+impl fmt::Write for List<char> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.extend(s.chars());
+        Ok(())
+    }
+}
+
+// Comparing a List against a Vec, slice or array walks both element-wise in head-to-tail order,
+// so `assert_eq!(list, vec![3, 2, 1])` works in tests. The orphan rules let us write the
+// symmetric impls too, because List (our local type) is the type parameter of PartialEq.
+impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for List<T> {
+    fn eq(&self, other: &Vec<U>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<&[U]> for List<T> {
+    fn eq(&self, other: &&[U]) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for List<T> {
+    fn eq(&self, other: &[U; N]) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U: PartialEq<T>> PartialEq<List<T>> for Vec<U> {
+    fn eq(&self, other: &List<T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U: PartialEq<T>> PartialEq<List<T>> for &[U] {
+    fn eq(&self, other: &List<T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U: PartialEq<T>, const N: usize> PartialEq<List<T>> for [U; N] {
+    fn eq(&self, other: &List<T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+// Write some tests. (Tests are generally written next to the code they support, but within a new
+// namespace).
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    
+    #[test]
+    fn basics() {
+        // We want to create a new list:
+        let mut list = List::new();
+
+        // Test empty list behavior:
+        assert_eq!(list.pop(), None);
+
+        // We want to use push functionality and test the pop functionality on non None values:
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+
+        // Push a few more things to ensure list is not corrupted:
+        list.push(4);
+        list.push(5);
+
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+
+        // Check list exhaustion:
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    #[allow(clippy::option_map_unit_fn)]
+    fn peek() {
+        //Test to see if peek works
+        let mut list = List::new();
+        
+        // Test that peek with no arguments returns none:
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+        
+        // Test that peeking at a list with some elements works as expected:
+        list.push(1); list.push(2); list.push(3);
+        assert_eq!(list.peek(), Some(&3));
+        assert_eq!(list.peek_mut(), Some(&mut 3));
+
+        // Test that the mutable peek can mutate the return value of peek_mut:
+        list.peek_mut().map(|value| {
+            *value = 42 
+            // The * is shorthand for dereference. 
+            //We are peeking at a value in the list, and dereferencing 
+            //the raw mutable pointer. Ex: peek at 3, dereference it and mutate it to be 42.
+        });
+
+        assert_eq!(list.peek(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+    }
+
+    #[test]
+    fn intoiter() {
+        let mut list = List::new();
+        list.push(0); list.push(1); list.push(2);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        list.push(0); list.push(1); list.push(2);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next(), None);
+
+        // `for` loops over a borrowed list go through IntoIterator for &List:
+        let mut total = 0;
+        for elem in &list {
+            total += *elem;
+        }
+        assert_eq!(total, 3);
+    }
+
+    #[test]
+    fn itermut() {
+        let mut list = List::new();
+        list.push(0); list.push(1); list.push(2);
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next(), Some(&mut 20));
+        assert_eq!(iter.next(), Some(&mut 10));
+        assert_eq!(iter.next(), Some(&mut 0));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn sort_by_cached_key() {
+        use std::cell::Cell;
+
+        let words = ["pear", "fig", "banana", "kiwi", "apple", "plum", "date"];
+        let mut list = List::new();
+        for word in words.iter().rev() {
+            list.push(word.to_string());
+        }
+
+        // Count how many times the key extractor runs:
+        let calls = Cell::new(0);
+        list.sort_by_cached_key(|s| {
+            calls.set(calls.get() + 1);
+            s.len()
+        });
+        assert_eq!(calls.get(), words.len());
+
+        // The order must match a (stable) sort_by_key over the same data:
+        let mut expected: Vec<String> = words.iter().map(|s| s.to_string()).collect();
+        expected.sort_by_key(|s| s.len());
+        let sorted: Vec<String> = list.into_iter().collect();
+        assert_eq!(sorted, expected);
+
+        // Sorting an empty list never calls the key extractor:
+        let mut empty: List<String> = List::new();
+        empty.sort_by_cached_key(|_| -> usize { panic!("no elements to compute keys for") });
+        assert_eq!(empty.peek(), None);
+    }
+
+    // A small deterministic pseudo random number generator so the sorting tests don't need the
+    // rand crate:
+    fn random_ints(n: usize, seed: u32) -> Vec<i32> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state % 1000) as i32
+            })
+            .collect()
+    }
+
+    fn list_from<T: Clone>(elems: &[T]) -> List<T> {
+        let mut list = List::new();
+        for elem in elems.iter().rev() {
+            list.push(elem.clone());
+        }
+        list
+    }
+
+    #[test]
+    fn sort_in_place() {
+        // 10,000 random elements (with lots of duplicates) sorted against the Vec version.
+        // Miri is a lot slower, so it sorts fewer.
+        let n = if cfg!(miri) { 300 } else { 10_000 };
+        let input = random_ints(n, 0x2545_f491);
+        let mut list = list_from(&input);
+        list.sort_in_place();
+
+        let mut expected = input;
+        expected.sort();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), expected);
+
+        // Already sorted and reverse sorted input:
+        let mut list = list_from(&(0..n as i32).collect::<Vec<_>>());
+        list.sort_in_place();
+        assert!(list.iter().zip(0..).all(|(&a, b)| a == b));
+
+        let mut list = list_from(&(0..n as i32).rev().collect::<Vec<_>>());
+        list.sort_in_place();
+        assert!(list.iter().zip(0..).all(|(&a, b)| a == b));
+
+        // Empty and single element lists are left alone:
+        let mut list: List<i32> = List::new();
+        list.sort_in_place();
+        assert_eq!(list.peek(), None);
+
+        let mut list = list_from(&[7]);
+        list.sort_in_place();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![7]);
+    }
+
+    #[test]
+    fn sort_in_place_is_stable() {
+        // Sort pairs by their first field only (the Ord impl below ignores the second field), the
+        // second field records the original position:
+        #[derive(Debug, PartialEq, Eq)]
+        struct Keyed(i32, usize);
+        impl PartialOrd for Keyed {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Keyed {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let keys = [3, 1, 3, 2, 1, 3];
+        let mut list = List::new();
+        for (i, &k) in keys.iter().enumerate().rev() {
+            list.push(Keyed(k, i));
+        }
+        list.sort_in_place();
+
+        let sorted: Vec<(i32, usize)> = list.into_iter().map(|k| (k.0, k.1)).collect();
+        assert_eq!(sorted, vec![(1, 1), (1, 4), (2, 3), (3, 0), (3, 2), (3, 5)]);
+    }
+
+    #[test]
+    fn eq_vec_slice_array() {
+        let mut list = List::new();
+        list.push(1); list.push(2); list.push(3);
+
+        // Equal contents, compared head to tail, from both sides:
+        assert_eq!(list, vec![3, 2, 1]);
+        assert_eq!(vec![3, 2, 1], list);
+        assert_eq!(list, &[3, 2, 1][..]);
+        assert_eq!(&[3, 2, 1][..], list);
+        assert_eq!(list, [3, 2, 1]);
+        assert_eq!([3, 2, 1], list);
+
+        // Differing lengths:
+        assert_ne!(list, vec![3, 2]);
+        assert_ne!(list, [3, 2, 1, 0]);
+        assert_ne!(list, &[0i32; 0][..]);
+
+        // Differing single element:
+        assert_ne!(list, vec![3, 0, 1]);
+        assert_ne!([3, 2, 0], list);
+
+        // Empty lists equal empty collections:
+        let empty: List<i32> = List::new();
+        assert_eq!(empty, Vec::<i32>::new());
+        assert_eq!(empty, [0i32; 0]);
+    }
+
+    #[test]
+    fn debug() {
+        let mut list = List::new();
+        assert_eq!(format!("{:?}", list), "[]");
+        list.push("b"); list.push("a");
+        assert_eq!(format!("{:?}", list), "[\"a\", \"b\"]");
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+
+        list.push(1); list.push(2);
+        assert_eq!(list.len(), 2);
+        assert!(!list.is_empty());
+    }
+
+    #[test]
+    fn insert_remove_split_off() {
+        let mut list = List::new();
+        list.insert(0, 'b');
+        list.insert(0, 'a');
+        list.insert(2, 'd'); // inserting at len appends
+        list.insert(2, 'c');
+        assert_eq!(list, ['a', 'b', 'c', 'd']);
+
+        assert_eq!(list.remove(1), 'b');
+        assert_eq!(list.remove(2), 'd');
+        assert_eq!(list, ['a', 'c']);
+
+        list.push('z');
+        let tail = list.split_off(1);
+        assert_eq!(list, ['z']);
+        assert_eq!(tail, ['a', 'c']);
+
+        // Splitting at 0 moves everything, splitting at len moves nothing:
+        let all = list.split_off(0);
+        assert!(list.is_empty());
+        assert_eq!(all, ['z']);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should be <= len (is 1)")]
+    fn insert_out_of_bounds() {
+        let mut list = List::new();
+        list.push(1);
+        list.insert(2, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 0) should be < len (is 0)")]
+    fn remove_out_of_bounds() {
+        let mut list: List<i32> = List::new();
+        list.remove(0);
+    }
+
+    #[test]
+    fn try_indexed_methods() {
+        use crate::errors::ListError;
+
+        let mut list: List<i32> = List::new();
+
+        // Errors carry the offending index and the length of the list:
+        assert_eq!(list.try_remove(0), Err(ListError::Empty));
+        assert_eq!(list.try_get(0), Err(ListError::Empty));
+        assert_eq!(list.try_insert(1, 5), Err(ListError::IndexOutOfBounds { index: 1, len: 0 }));
+        assert_eq!(
+            list.try_split_off(3).unwrap_err(),
+            ListError::IndexOutOfBounds { index: 3, len: 0 }
+        );
+
+        // The Ok paths do exactly what the panicking versions do:
+        let mut expected = List::new();
+        for (i, elem) in [10, 20, 30].iter().enumerate() {
+            assert_eq!(list.try_insert(i, *elem), Ok(()));
+            expected.insert(i, *elem);
+        }
+        assert_eq!(list, [10, 20, 30]);
+        assert_eq!(expected, [10, 20, 30]);
+
+        assert_eq!(list.try_get(2), Ok(&30));
+        assert_eq!(list.try_get(3), Err(ListError::IndexOutOfBounds { index: 3, len: 3 }));
+
+        assert_eq!(list.try_remove(1), Ok(expected.remove(1)));
+        assert_eq!(list.try_remove(5), Err(ListError::IndexOutOfBounds { index: 5, len: 2 }));
+        assert_eq!(list, [10, 30]);
+
+        let tail = list.try_split_off(1).unwrap();
+        assert_eq!(tail, expected.split_off(1).into_iter().collect::<Vec<_>>());
+        assert_eq!(list, [10]);
+
+        assert_eq!(
+            ListError::IndexOutOfBounds { index: 3, len: 2 }.to_string(),
+            "index 3 is out of bounds for a list of length 2"
+        );
+        assert_eq!(ListError::Empty.to_string(), "the list is empty");
+    }
+
+    #[test]
+    fn merge_k_sorted() {
+        use super::merge_k_sorted;
+
+        // Five sorted lists of different lengths, one of them empty:
+        let mut lists = List::new();
+        for elems in [
+            &[1, 4, 9][..],
+            &[2, 3, 5, 7, 11, 13],
+            &[],
+            &[0, 4, 4, 20],
+            &[6],
+        ]
+        .iter()
+        {
+            lists.push(list_from(elems));
+        }
+
+        let merged = merge_k_sorted(lists);
+        assert_eq!(merged, [0, 1, 2, 3, 4, 4, 4, 5, 6, 7, 9, 11, 13, 20]);
+
+        // Only empty lists (and no lists at all) give an empty list:
+        let mut lists = List::new();
+        lists.push(List::<i32>::new());
+        lists.push(List::new());
+        assert!(merge_k_sorted(lists).is_empty());
+        assert!(merge_k_sorted(List::<List<i32>>::new()).is_empty());
+
+        // A single list comes back unchanged:
+        let mut lists = List::new();
+        lists.push(list_from(&[1, 2, 2, 8]));
+        assert_eq!(merge_k_sorted(lists), [1, 2, 2, 8]);
+    }
+
+    #[test]
+    fn merge_k_sorted_from_any_iterator() {
+        // No lists, and one list:
+        assert!(List::<i32>::merge_k_sorted(Vec::new()).is_empty());
+        assert_eq!(List::merge_k_sorted(Some(list_from(&[3, 5, 5]))), [3, 5, 5]);
+
+        // Interleaved values with duplicates, within and across lists:
+        let lists = vec![list_from(&[1, 3, 5, 7]), list_from(&[1, 2, 3, 3]), list_from(&[0, 7, 8])];
+        assert_eq!(List::merge_k_sorted(lists), [0, 1, 1, 2, 3, 3, 3, 5, 7, 7, 8]);
+
+        // Same as sorting everything together:
+        let mut all = Vec::new();
+        let lists: Vec<List<i32>> = (0..10)
+            .map(|seed| {
+                let mut ints = random_ints(seed as usize * 7, seed + 1);
+                ints.sort_unstable();
+                all.extend_from_slice(&ints);
+                list_from(&ints)
+            })
+            .collect();
+        all.sort_unstable();
+        assert_eq!(List::merge_k_sorted(lists), all);
+
+        // Equal elements keep the order of their lists (the merge is stable):
+        let lists = vec![list_from(&[Key(1, 'a'), Key(2, 'a')]), list_from(&[Key(1, 'b'), Key(2, 'b')])];
+        let merged = List::merge_k_sorted(lists);
+        assert_eq!(merged.iter().map(|Key(_, c)| *c).collect::<String>(), "abab");
+    }
+
+    // Compares by the number only, so equal keys can be told apart by their tag.
+    #[derive(Debug, Clone)]
+    struct Key(i32, char);
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Key {}
+
+    impl PartialOrd for Key {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Key {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut list = list_from(&[0, 10, 20, 30, 40]);
+
+        // Two valid indices, in either order, point at the right elements:
+        let [a, b] = list.get_many_mut([1, 3]).unwrap();
+        assert_eq!((*a, *b), (10, 30));
+        let [a, b] = list.get_many_mut([4, 0]).unwrap();
+        assert_eq!((*a, *b), (40, 0));
+
+        // Mutating through both references at once, e.g. a swap:
+        let [a, b] = list.get_many_mut([0, 4]).unwrap();
+        std::mem::swap(a, b);
+        *list.get_many_mut([2]).unwrap()[0] += 1;
+        assert_eq!(list, [40, 10, 21, 30, 0]);
+
+        // A duplicate index or an out of range index gives None:
+        assert!(list.get_many_mut([1, 1]).is_none());
+        assert!(list.get_many_mut([2, 5]).is_none());
+        assert!(list.get_many_mut([0, 1, 2, 3, 4, 5]).is_none());
+
+        // Asking for nothing always works:
+        assert!(list.get_many_mut([]).is_some());
+    }
+
+    #[test]
+    fn count_inversions() {
+        // A sorted list has none:
+        let list = list_from(&(0..100).collect::<Vec<_>>());
+        assert_eq!(list.count_inversions(), 0);
+
+        // A reverse sorted list of n elements has every pair inverted, n * (n - 1) / 2:
+        let n = 100;
+        let list = list_from(&(0..n as i32).rev().collect::<Vec<_>>());
+        assert_eq!(list.count_inversions(), n * (n - 1) / 2);
+
+        // A random list matches the brute force count:
+        let input = random_ints(500, 0xdead_beef);
+        let mut brute_force = 0;
+        for i in 0..input.len() {
+            for j in i + 1..input.len() {
+                if input[i] > input[j] {
+                    brute_force += 1;
+                }
+            }
+        }
+        let list = list_from(&input);
+        assert_eq!(list.count_inversions(), brute_force);
+
+        // The list itself is not sorted by counting:
+        assert_eq!(list, input);
+
+        assert_eq!(List::<i32>::new().count_inversions(), 0);
+    }
+
+    #[test]
+    fn retain() {
+        let mut list = list_from(&[1, 2, 3, 4, 5, 6]);
+        list.retain(|&x| x % 2 == 0);
+        assert_eq!(list, [2, 4, 6]);
+
+        // Removing the head and the tail:
+        list.retain(|&x| x == 4);
+        assert_eq!(list, [4]);
+
+        list.retain(|_| false);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn retain_mut() {
+        use std::cell::RefCell;
+
+        // Each element is a (name, counter) pair that logs its name when it is dropped:
+        struct Ticket<'a> {
+            name: char,
+            count: u32,
+            dropped: &'a RefCell<Vec<char>>,
+        }
+        impl Drop for Ticket<'_> {
+            fn drop(&mut self) {
+                self.dropped.borrow_mut().push(self.name);
+            }
+        }
+
+        let dropped = RefCell::new(Vec::new());
+        let mut list = List::new();
+        for (name, count) in [('a', 1), ('b', 3), ('c', 1), ('d', 2), ('e', 1)].iter().rev() {
+            list.push(Ticket { name: *name, count: *count, dropped: &dropped });
+        }
+
+        // Decrement every counter and drop the ones that hit zero, in a single pass:
+        list.retain_mut(|ticket| {
+            ticket.count -= 1;
+            ticket.count > 0
+        });
+
+        let survivors: Vec<(char, u32)> = list.iter().map(|t| (t.name, t.count)).collect();
+        assert_eq!(survivors, vec![('b', 2), ('d', 1)]);
+        assert_eq!(*dropped.borrow(), vec!['a', 'c', 'e']);
+    }
+
+    #[test]
+    fn retain_mut_decides_after_mutating() {
+        // Negative numbers are clamped to 0 and kept, anything over 100 is dropped, all in one
+        // pass: the kept elements keep the change made while deciding.
+        let mut list = List::from([-5, 50, 101, -500, 100, 250, 0]);
+        list.retain_mut(|x| {
+            *x = (*x).max(0);
+            *x <= 100
+        });
+        assert_eq!(list, [0, 50, 0, 100, 0]);
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn drain_range() {
+        let mut list = list_from(&[0, 1, 2, 3, 4, 5, 6]);
+
+        // A middle range, the list is closed around the gap:
+        assert_eq!(list.drain_range(2..4).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(list, [0, 1, 4, 5, 6]);
+
+        // Inclusive and unbounded ranges:
+        assert_eq!(list.drain_range(..=1).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(list.drain_range(2..).collect::<Vec<_>>(), vec![6]);
+        assert_eq!(list, [4, 5]);
+
+        // An empty range removes nothing:
+        assert_eq!(list.drain_range(1..1).count(), 0);
+        assert_eq!(list, [4, 5]);
+
+        // The whole list:
+        assert_eq!(list.drain().collect::<Vec<_>>(), vec![4, 5]);
+        assert!(list.is_empty());
+        assert_eq!(list.drain_range(..).count(), 0);
+    }
+
+    #[test]
+    fn drain_range_dropped_early() {
+        use std::cell::Cell;
+
+        struct DropCounter<'a>(i32, &'a Cell<usize>);
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for i in (0..6).rev() {
+            list.push(DropCounter(i, &drops));
+        }
+
+        // Take a single element out of a four element drain, then drop the iterator:
+        let mut drain = list.drain_range(1..5);
+        assert_eq!(drain.next().map(|d| d.0), Some(1));
+        assert_eq!(drops.get(), 1);
+        drop(drain);
+
+        // The other three ranged elements were removed (and dropped) anyway:
+        assert_eq!(drops.get(), 4);
+        assert_eq!(list.iter().map(|d| d.0).collect::<Vec<_>>(), vec![0, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start index 3 is greater than end index 2")]
+    fn drain_range_start_after_end() {
+        let mut list = list_from(&[1, 2, 3, 4]);
+        #[allow(clippy::reversed_empty_ranges)]
+        list.drain_range(3..2);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 5 is out of range for a list of length 4")]
+    fn drain_range_past_the_end() {
+        let mut list = list_from(&[1, 2, 3, 4]);
+        list.drain_range(1..5);
+    }
+
+    #[test]
+    fn from_iter_and_extend() {
+        // Collecting keeps the iterator's order, head first:
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list, [1, 2, 3]);
+
+        // Extending appends at the tail:
+        list.extend(vec![4, 5]);
+        assert_eq!(list, [1, 2, 3, 4, 5]);
+
+        let mut empty: List<i32> = std::iter::empty().collect();
+        assert!(empty.is_empty());
+        empty.extend(Some(9));
+        assert_eq!(empty, [9]);
+    }
+
+    #[test]
+    fn splice() {
+        // Replacing the head range with a longer replacement:
+        let mut list = list_from(&[1, 2, 3, 4, 5]);
+        let removed = list.splice(..2, vec![10, 20, 30]);
+        assert_eq!(removed, [1, 2]);
+        assert_eq!(list, [10, 20, 30, 3, 4, 5]);
+
+        // A middle range with a shorter replacement:
+        let removed = list.splice(1..4, Some(0));
+        assert_eq!(removed, [20, 30, 3]);
+        assert_eq!(list, [10, 0, 4, 5]);
+
+        // The tail, replaced with nothing:
+        let removed = list.splice(2.., None);
+        assert_eq!(removed, [4, 5]);
+        assert_eq!(list, [10, 0]);
+
+        // An empty range only inserts:
+        let removed = list.splice(1..1, vec![7, 8]);
+        assert!(removed.is_empty());
+        assert_eq!(list, [10, 7, 8, 0]);
+
+        // Splicing at the very end appends:
+        list.splice(4.., vec![9]);
+        assert_eq!(list, [10, 7, 8, 0, 9]);
+
+        // And into an empty list:
+        let mut empty: List<i32> = List::new();
+        assert!(empty.splice(.., vec![1, 2]).is_empty());
+        assert_eq!(empty, [1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 3 is out of range for a list of length 2")]
+    fn splice_out_of_range() {
+        let mut list = list_from(&[1, 2]);
+        list.splice(1..3, None);
+    }
+
+    #[test]
+    fn move_to_front() {
+        let mut list = list_from(&['a', 'b', 'c', 'd', 'e']);
+
+        // Looking things up moves them to the front, the rest keep their relative order:
+        assert!(list.move_to_front(|&c| c == 'd'));
+        assert_eq!(list, ['d', 'a', 'b', 'c', 'e']);
+
+        // Matching the tail:
+        assert!(list.move_to_front(|&c| c == 'e'));
+        assert_eq!(list, ['e', 'd', 'a', 'b', 'c']);
+
+        // Matching the head is a no-op that still reports a match:
+        assert!(list.move_to_front(|&c| c == 'e'));
+        assert_eq!(list, ['e', 'd', 'a', 'b', 'c']);
+
+        // A "hot" element that keeps getting looked up stays at the front, and the two hot
+        // elements end up ahead of every cold one:
+        for &c in ['b', 'c', 'b', 'c', 'b'].iter() {
+            list.move_to_front(|&x| x == c);
+        }
+        assert_eq!(list, ['b', 'c', 'e', 'd', 'a']);
+
+        // No match leaves the list untouched:
+        assert!(!list.move_to_front(|&c| c == 'z'));
+        assert_eq!(list, ['b', 'c', 'e', 'd', 'a']);
+
+        let mut empty: List<char> = List::new();
+        assert!(!empty.move_to_front(|_| true));
+    }
+
+    #[test]
+    fn repeat() {
+        let list = list_from(&[1, 2, 3]);
+
+        let repeated = list.repeat(3);
+        assert_eq!(repeated.len(), 3 * list.len());
+        assert_eq!(repeated, [1, 2, 3, 1, 2, 3, 1, 2, 3]);
+
+        // Zero times is empty, once is a clone:
+        assert!(list.repeat(0).is_empty());
+        assert_eq!(list.repeat(1), [1, 2, 3]);
+        assert!(List::<i32>::new().repeat(5).is_empty());
+
+        // The original is untouched:
+        assert_eq!(list, [1, 2, 3]);
+
+        // Matches slice::repeat:
+        let ints = random_ints(10, 7);
+        let expected = ints.repeat(4);
+        assert_eq!(list_from(&ints).repeat(4), expected);
+    }
+
+    #[test]
+    fn cartesian_product() {
+        use itertools::Itertools;
+
+        let numbers = list_from(&[1, 2, 3]);
+        let letters = list_from(&['a', 'b']);
+
+        let pairs = numbers.cartesian_product(&letters);
+        assert_eq!(pairs.len(), numbers.len() * letters.len());
+        assert_eq!(pairs, [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b'), (3, 'a'), (3, 'b')]);
+
+        // Same pairs, same order as itertools:
+        let (xs, ys) = (random_ints(7, 3), random_ints(5, 11));
+        let expected: Vec<_> = xs.iter().copied().cartesian_product(ys.iter().copied()).collect();
+        assert_eq!(list_from(&xs).cartesian_product(&list_from(&ys)), expected);
+
+        // Either side empty gives nothing:
+        let empty: List<i32> = List::new();
+        assert!(empty.cartesian_product(&letters).is_empty());
+        assert!(numbers.cartesian_product(&List::<char>::new()).is_empty());
+    }
+
+    #[test]
+    fn split_into() {
+        let ints = random_ints(10, 5);
+
+        for n in 1..=13 {
+            let parts = list_from(&ints).split_into(n);
+            assert_eq!(parts.len(), n);
+
+            // Lengths differ by at most one, and the longer parts come first:
+            let lens: Vec<usize> = parts.iter().map(List::len).collect();
+            assert!(lens.windows(2).all(|w| w[0] >= w[1] && w[0] - w[1] <= 1));
+            assert_eq!(lens.iter().sum::<usize>(), ints.len());
+
+            // Gluing the parts back together gives the original sequence:
+            let joined: Vec<i32> = parts.into_iter().flatten().collect();
+            assert_eq!(joined, ints);
+        }
+
+        let lens: Vec<usize> = list_from(&[1, 2, 3, 4, 5, 6, 7]).split_into(3).iter().map(List::len).collect();
+        assert_eq!(lens, [3, 2, 2]);
+
+        // More parts than elements leaves empty lists at the end:
+        let parts = list_from(&[1, 2]).split_into(4);
+        assert_eq!(parts[0], [1]);
+        assert_eq!(parts[1], [2]);
+        assert!(parts[2].is_empty() && parts[3].is_empty());
+
+        assert!(List::<i32>::new().split_into(2).iter().all(List::is_empty));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot split a list into 0 parts")]
+    fn split_into_zero_parts() {
+        list_from(&[1, 2, 3]).split_into(0);
+    }
+
+    #[test]
+    fn zip_longest() {
+        // self longer:
+        let zipped = list_from(&[1, 2, 3]).zip_longest(list_from(&['a']));
+        assert_eq!(zipped, [(Some(1), Some('a')), (Some(2), None), (Some(3), None)]);
+
+        // other longer:
+        let zipped = list_from(&[1]).zip_longest(list_from(&['a', 'b', 'c']));
+        assert_eq!(zipped, [(Some(1), Some('a')), (None, Some('b')), (None, Some('c'))]);
+
+        // Equal lengths never pad:
+        let zipped = list_from(&[1, 2]).zip_longest(list_from(&['a', 'b']));
+        assert!(zipped.iter().all(|(a, b)| a.is_some() && b.is_some()));
+        assert_eq!(zipped.len(), 2);
+
+        assert!(List::<i32>::new().zip_longest(List::<char>::new()).is_empty());
+    }
+
+    #[test]
+    fn zip_longest_with() {
+        // Equal lengths give the same pairs as zip:
+        let zipped = List::from([1, 2]).zip_longest_with(List::from(['a', 'b']), 0, '-');
+        let zip: Vec<_> = [1, 2].iter().copied().zip(['a', 'b'].iter().copied()).collect();
+        assert_eq!(zipped, zip);
+
+        // Shorter first list, padded with the T default:
+        let zipped = List::from([1]).zip_longest_with(List::from(['a', 'b', 'c']), 0, '-');
+        assert_eq!(zipped, [(1, 'a'), (0, 'b'), (0, 'c')]);
+
+        // Shorter second list, padded with the U default:
+        let zipped = List::from([1, 2, 3]).zip_longest_with(List::from(['a']), 0, '-');
+        assert_eq!(zipped, [(1, 'a'), (2, '-'), (3, '-')]);
+
+        // One side empty is all defaults on that side:
+        let zipped = List::new().zip_longest_with(List::from(['a', 'b']), 0, '-');
+        assert_eq!(zipped, [(0, 'a'), (0, 'b')]);
+    }
+
+    #[test]
+    fn has_at_least() {
+        let list = list_from(&[1, 2, 3]);
+        assert!(list.has_at_least(0));
+        assert!(list.has_at_least(2));
+        assert!(list.has_at_least(3));
+        assert!(!list.has_at_least(4));
+        assert!(List::<i32>::new().has_at_least(0));
+        assert!(!List::<i32>::new().has_at_least(1));
+
+        // The answers follow the list as it changes:
+        let mut list = list;
+        list.push(0);
+        assert!(list.has_at_least(4));
+        list.pop();
+        list.pop();
+        assert!(!list.has_at_least(3));
+    }
+
+    #[test]
+    fn len_cmp() {
+        use std::cmp::Ordering;
+
+        let (short, long) = (list_from(&[1, 2]), list_from(&[1, 2, 3]));
+        assert_eq!(short.len_cmp(&long), Ordering::Less);
+        assert_eq!(long.len_cmp(&short), Ordering::Greater);
+        assert_eq!(long.len_cmp(&list_from(&[7, 8, 9])), Ordering::Equal);
+        assert_eq!(List::<i32>::new().len_cmp(&List::new()), Ordering::Equal);
+        assert_eq!(List::new().len_cmp(&short), Ordering::Less);
+
+        let mut growing = list_from(&[1, 2]);
+        growing.push(0);
+        assert_eq!(growing.len_cmp(&long), Ordering::Equal);
+        growing.push(0);
+        assert_eq!(growing.len_cmp(&long), Ordering::Greater);
+    }
+
+    #[test]
+    fn scan() {
+        let totals = list_from(&[1, 2, 3]).scan(0, |total, x| {
+            *total += x;
+            *total
+        });
+        assert_eq!(totals, [1, 3, 6]);
+
+        let maxima = list_from(&[3, 1, 4, 1, 5, 9, 2, 6]).scan(i32::MIN, |max, x| {
+            *max = (*max).max(x);
+            *max
+        });
+        assert_eq!(maxima, [3, 3, 4, 4, 5, 9, 9, 9]);
+
+        // The emitted type can differ from the element and state types:
+        let labels = list_from(&['a', 'b', 'c']).scan(1, |n, c| {
+            let label = format!("{}{}", c, n);
+            *n += 1;
+            label
+        });
+        assert_eq!(labels, ["a1", "b2", "c3"]);
+
+        let empty: List<i32> = List::new().scan(0, |total: &mut i32, x: i32| *total + x);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn slice() {
+        let ints = random_ints(10, 9);
+        let list = list_from(&ints);
+
+        assert_eq!(list.slice(..), ints);
+        assert_eq!(list.slice(..3), &ints[..3]);
+        assert_eq!(list.slice(7..), &ints[7..]);
+        assert_eq!(list.slice(2..=5), &ints[2..=5]);
+        assert!(list.slice(4..4).is_empty());
+        assert!(list.slice(10..).is_empty());
+
+        // The original is untouched:
+        assert_eq!(list, ints);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 4 is out of range for a list of length 3")]
+    fn slice_out_of_range() {
+        list_from(&[1, 2, 3]).slice(1..4);
+    }
+
+    #[test]
+    fn step_by() {
+        let ten: List<i32> = (1..=10).collect();
+        assert_eq!(ten.iter().step_by(2).copied().collect::<Vec<_>>(), [1, 3, 5, 7, 9]);
+        assert_eq!(ten.iter().step_by(3).copied().collect::<Vec<_>>(), [1, 4, 7, 10]);
+        assert_eq!(ten.iter().step_by(1).count(), 10);
+
+        let five = list_from(&[1, 2, 3, 4, 5]);
+        assert_eq!(five.iter().step_by(10).collect::<Vec<_>>(), [&1]);
+        assert_eq!(List::<i32>::new().iter().step_by(2).next(), None);
+
+        // Same as the standard library's step_by:
+        for n in 1..12 {
+            let ours: Vec<_> = ten.iter().step_by(n).collect();
+            let std: Vec<_> = Iterator::step_by(ten.iter(), n).collect();
+            assert_eq!(ours, std);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "step_by step must not be 0")]
+    fn step_by_zero() {
+        list_from(&[1, 2]).iter().step_by(0);
+    }
+
+    #[test]
+    fn extend_from_references() {
+        let mut list = list_from(&[1, 2]);
+        let more: &[i32] = &[3, 4, 5];
+        list.extend(more.iter());
+        assert_eq!(list, [1, 2, 3, 4, 5]);
+
+        list.extend(&[6]);
+        assert_eq!(list, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn from_array() {
+        let list = List::from([1, 2, 3]);
+        assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+        assert_eq!(list.peek(), Some(&1));
+
+        // Works for types that aren't Clone too:
+        let boxed = List::from([Box::new('a'), Box::new('b')]);
+        assert_eq!(boxed.len(), 2);
+
+        let empty: List<i32> = List::from([]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn intersperse() {
+        let commas: String = List::from(['a', 'b', 'c']).intersperse(',').into_iter().collect();
+        assert_eq!(commas, "a,b,c");
+
+        assert_eq!(List::from([1, 2, 3, 4]).intersperse(0), [1, 0, 2, 0, 3, 0, 4]);
+
+        for n in 1..10 {
+            let list: List<usize> = (0..n).collect();
+            assert_eq!(list.intersperse(99).len(), 2 * n - 1);
+        }
+
+        // Nothing to put a separator between:
+        assert_eq!(List::from([7]).intersperse(0), [7]);
+        assert!(List::<i32>::new().intersperse(0).is_empty());
+    }
+
+    #[test]
+    fn borrowed_lookups() {
+        let words = List::from([String::from("hello"), String::from("world")]);
+        assert!(words.contains_borrowed("hello"));
+        assert!(!words.contains_borrowed("nope"));
+        let found: Option<&String> = words.find_borrowed("world");
+        assert_eq!(found.map(String::as_str), Some("world"));
+        assert_eq!(words.find_borrowed("nope"), None);
+
+        let bytes = List::from([vec![1u8, 2], vec![3]]);
+        assert!(bytes.contains_borrowed(&[3u8][..]));
+        assert_eq!(bytes.find_borrowed(&[1u8, 2][..]), Some(&vec![1, 2]));
+        assert!(!bytes.contains_borrowed(&[][..]));
+
+        // Plain T works too, every type borrows as itself:
+        assert!(List::from([1, 2]).contains_borrowed(&2));
+        assert!(!List::<i32>::new().contains_borrowed(&2));
+    }
+
+    #[test]
+    fn join() {
+        let list = List::from([1, 2, 3]);
+        assert_eq!(list.join(", "), "1, 2, 3");
+        assert_eq!(list.join(""), "123");
+        assert_eq!(list.join(", "), [1, 2, 3].map(|x| x.to_string()).join(", "));
+
+        assert_eq!(List::from([4.5]).join(" + "), "4.5");
+        assert_eq!(List::<i32>::new().join(", "), "");
+    }
+
+    // Records its id into a shared log when it is dropped.
+    struct DropRecorder(u32, Rc<RefCell<Vec<u32>>>);
+
+    impl Drop for DropRecorder {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    fn recorders(log: &Rc<RefCell<Vec<u32>>>, n: u32) -> List<DropRecorder> {
+        (0..n).map(|id| DropRecorder(id, log.clone())).collect()
+    }
+
+    #[test]
+    fn drop_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        // Dropping the list, and clearing it, both go head to tail:
+        drop(recorders(&log, 5));
+        assert_eq!(*log.borrow(), [0, 1, 2, 3, 4]);
+
+        log.borrow_mut().clear();
+        let mut list = recorders(&log, 3);
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(*log.borrow(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn clear_with() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut list = recorders(&log, 4);
+
+        let mut seen = Vec::new();
+        list.clear_with(|recorder| seen.push(recorder.0));
+        assert_eq!(seen, [0, 1, 2, 3]);
+        assert!(list.is_empty());
+        // Each element was dropped at the end of its callback call:
+        assert_eq!(*log.borrow(), [0, 1, 2, 3]);
+
+        // The list is still usable afterwards:
+        list.push(DropRecorder(9, log.clone()));
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn clear_with_panicking_callback() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut list = recorders(&log, 5);
+
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.clear_with(|recorder| {
+                calls += 1;
+                if recorder.0 == 2 {
+                    panic!("callback failed");
+                }
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        // The elements after the failing one are still in the list, and are dropped with it:
+        assert_eq!(list.len(), 2);
+        drop(list);
+        assert_eq!(*log.borrow(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn cycle() {
+        let list = List::from(['a', 'b', 'c']);
+        let round_robin: String = list.iter().cycle().take(7).collect();
+        assert_eq!(round_robin, "abcabca");
+        assert_eq!(list.iter().cycle().take(0).count(), 0);
+        assert_eq!(List::from([1]).iter().cycle().take(3).collect::<Vec<_>>(), [&1, &1, &1]);
+
+        // Cycling an empty list ends right away instead of looping forever:
+        assert_eq!(List::<i32>::new().iter().cycle().next(), None);
+
+        // Cycling starts from wherever the iterator already was:
+        let mut iter = list.iter();
+        iter.next();
+        assert_eq!(iter.cycle().take(4).collect::<String>(), "bcbc");
+    }
+
+    // The manual Send and Sync impls have exactly the bounds of Box<T> and Vec<T>:
+    static_assertions::assert_impl_all!(List<i32>: Send, Sync);
+    static_assertions::assert_not_impl_any!(List<Rc<i32>>: Send, Sync);
+    static_assertions::assert_impl_all!(List<std::cell::Cell<i32>>: Send);
+    static_assertions::assert_not_impl_any!(List<std::cell::Cell<i32>>: Sync);
+    // A MutexGuard may be shared, but not sent:
+    static_assertions::assert_impl_all!(List<std::sync::MutexGuard<'static, i32>>: Sync);
+    static_assertions::assert_not_impl_any!(List<std::sync::MutexGuard<'static, i32>>: Send);
+    // And the iterators follow: IterMut hands out &mut T, so sending it is like sending T.
+    static_assertions::assert_not_impl_any!(super::Iter<'static, std::cell::Cell<i32>>: Send);
+    static_assertions::assert_impl_all!(super::IterMut<'static, std::cell::Cell<i32>>: Send);
+    static_assertions::assert_not_impl_any!(super::IntoIter<Rc<i32>>: Send);
+
+    // Cycle shares the elements, so it can only go to another thread if T is Sync:
+    static_assertions::assert_impl_all!(super::Cycle<'static, i32>: Send);
+    static_assertions::assert_not_impl_any!(super::Cycle<'static, std::cell::Cell<i32>>: Send);
+
+    #[test]
+    fn allocations_per_push() {
+        use crate::alloc_counter::measure;
+
+        // Every push is exactly one allocation, there is no spare capacity like in a Vec:
+        let mut list = List::new();
+        let ((), pushes) = measure(|| {
+            for i in 0..100u64 {
+                list.push(i);
+            }
+        });
+        assert_eq!(pushes.allocations, 100);
+
+        // Walking the list allocates nothing:
+        let (sum, walk) = measure(|| list.iter().sum::<u64>());
+        assert_eq!(sum, 4950);
+        assert_eq!(walk.allocations, 0);
+
+        // Popping frees the nodes again:
+        let ((), pops) = measure(|| while list.pop().is_some() {});
+        assert_eq!(pops.allocations, 0);
+        assert_eq!(pops.live_bytes, -pushes.live_bytes);
+    }
+
+    #[test]
+    fn lazy_chain_allocates_only_the_result() {
+        use crate::alloc_counter::measure;
+
+        let list: List<u32> = (0..100).collect();
+        // map and filter build nothing in between, so the only allocations are the result's nodes:
+        let (evens, chained) =
+            measure(|| list.lazy_chain().map(|x| x * 3).filter(|x| x % 2 == 0).collect::<List<_>>());
+        assert_eq!(evens.len(), 50);
+        assert_eq!(chained.allocations, 50);
+        assert_eq!(evens.iter().take(3).collect::<Vec<_>>(), [&0, &6, &12]);
+
+        // And without a collect at the end, nothing at all:
+        let (total, folded) =
+            measure(|| list.lazy_chain().map(|x| x * 3).filter(|x| x % 2 == 0).sum::<u32>());
+        assert_eq!(total, evens.iter().sum::<u32>());
+        assert_eq!(folded.allocations, 0);
+    }
+
+    #[test]
+    fn heap_size() {
+        use crate::alloc_counter::measure;
+        use std::mem::size_of;
+
+        let (list, allocated) = measure(|| {
+            let mut list = List::new();
+            for i in 0..50u32 {
+                list.push(i);
+            }
+            list
+        });
+        assert_eq!(list.heap_size() as isize, allocated.live_bytes);
+        assert_eq!(list.heap_size(), 50 * size_of::<super::Node<u32>>());
+        assert_eq!(List::<u32>::new().heap_size(), 0);
+
+        // The next pointer (and padding) makes every node bigger than its element:
+        assert!(list.heap_size() > list.len() * size_of::<u32>());
+        assert_eq!(List::from([1u8]).heap_size(), 2 * size_of::<usize>());
+    }
+
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    #[test]
+    fn invariants_hold() {
+        // Every mutating method already checks itself in debug builds; this runs a mix of them
+        // and checks the end result once more.
+        let mut list: List<i32> = (0..20).collect();
+        list.insert(5, 100);
+        list.remove(0);
+        list.retain(|x| x % 3 != 0);
+        list.drain_range(2..4);
+        list.splice(1..2, vec![7, 8, 9]);
+        let mut back = list.split_off(4);
+        back.sort_in_place();
+        list.move_to_front(|&x| x == 8);
+        let list = List::merge_k_sorted(vec![list, back]).intersperse(0);
+        list.assert_invariants();
+        assert_eq!(list.len(), list.iter().count());
+    }
+
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    #[test]
+    #[should_panic(expected = "list invariant violated: cached length is 5 but the list has 3 nodes")]
+    fn invariants_catch_wrong_length() {
+        let mut list = List::from([1, 2, 3]);
+        list.corrupt_len(5);
+        list.assert_invariants();
+    }
+
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    #[test]
+    #[should_panic(expected = "list invariant violated")]
+    fn mutating_a_corrupt_list_panics() {
+        // The check at the end of insert notices the damage:
+        let mut list = List::from([1, 2, 3]);
+        list.corrupt_len(2);
+        list.insert(1, 4);
+    }
+
+    #[test]
+    fn interleave() {
+        let list = List::from([1, 3, 5]).interleave(List::from([2, 4, 6]));
+        assert_eq!(list, [1, 2, 3, 4, 5, 6]);
+
+        // The longer list's leftovers come after the alternating part, either way round:
+        let list = List::from([1, 2]).interleave(List::from([10, 20, 30, 40, 50]));
+        assert_eq!(list, [1, 10, 2, 20, 30, 40, 50]);
+        assert_eq!(list.len(), 7);
+        let list = List::from([10, 20, 30, 40, 50]).interleave(List::from([1, 2]));
+        assert_eq!(list, [10, 1, 20, 2, 30, 40, 50]);
+
+        // An empty side gives back the other one:
+        assert_eq!(List::new().interleave(List::from([1, 2, 3])), [1, 2, 3]);
+        assert_eq!(List::from([1, 2, 3]).interleave(List::new()), [1, 2, 3]);
+        assert!(List::<i32>::new().interleave(List::new()).is_empty());
+    }
+
+    #[test]
+    fn windows() {
+        let list = List::from([1, 2, 3, 4, 5]);
+
+        let pairs: Vec<List<i32>> = list.windows(2).collect();
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(pairs[0], [1, 2]);
+        assert_eq!(pairs[1], [2, 3]);
+        assert_eq!(pairs[3], [4, 5]);
+
+        // A window as long as the list is the whole list, a longer one doesn't fit at all:
+        let whole: Vec<List<i32>> = list.windows(5).collect();
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0], [1, 2, 3, 4, 5]);
+        assert_eq!(list.windows(6).count(), 0);
+        assert_eq!(List::<i32>::new().windows(1).count(), 0);
+
+        // The list itself is untouched:
+        assert_eq!(list, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must not be 0")]
+    fn windows_of_size_zero() {
+        let _ = List::from([1, 2, 3]).windows(0);
+    }
+
+    #[test]
+    fn selection_sort() {
+        let random = random_ints(300, 0x1234_5678);
+        let cases: Vec<Vec<i32>> = vec![
+            random,
+            (0..100).rev().collect(),
+            (0..100).collect(),
+            vec![3, 1, 3, 2, 1, 3, 2, 2],
+            vec![7],
+            vec![],
+        ];
+        for case in cases {
+            let mut list = list_from(&case);
+            list.selection_sort();
+            let mut expected = case.clone();
+            expected.sort();
+            assert_eq!(list, expected);
+            assert_eq!(list.len(), case.len());
+        }
+
+        // Stable, like sort_in_place: Key only compares its number.
+        let mut list = List::from([Key(2, 'a'), Key(1, 'b'), Key(2, 'c'), Key(1, 'd')]);
+        list.selection_sort();
+        assert_eq!(list.iter().map(|key| key.1).collect::<String>(), "bdac");
+    }
+
+    #[test]
+    fn bubble_sort() {
+        let cases: Vec<Vec<i32>> = vec![
+            random_ints(300, 0x1234_5678),
+            (0..100).rev().collect(),
+            (0..100).collect(),
+            vec![3, 1, 3, 2, 1, 3, 2, 2],
+            vec![7],
+            vec![],
+        ];
+        for case in cases {
+            let mut list = list_from(&case);
+            list.bubble_sort();
+            let mut expected = case.clone();
+            expected.sort();
+            assert_eq!(list, expected);
+            assert_eq!(list.len(), case.len());
+        }
+
+        let mut list = List::from([Key(2, 'a'), Key(1, 'b'), Key(2, 'c'), Key(1, 'd')]);
+        list.bubble_sort();
+        assert_eq!(list.iter().map(|key| key.1).collect::<String>(), "bdac");
+    }
+
+    #[test]
+    fn odd_even_separate() {
+        let mut list = List::from([1, 2, 3, 4, 5, 6]);
+        list.odd_even_separate();
+        assert_eq!(list, [1, 3, 5, 2, 4, 6]);
+
+        // Odd length, and values don't matter, only positions:
+        let mut list = List::from(['a', 'b', 'c', 'd', 'e']);
+        list.odd_even_separate();
+        assert_eq!(list, ['a', 'c', 'e', 'b', 'd']);
+
+        // Nothing to do for up to two elements:
+        let mut list: List<i32> = List::new();
+        list.odd_even_separate();
+        assert!(list.is_empty());
+        let mut list = List::from([1]);
+        list.odd_even_separate();
+        assert_eq!(list, [1]);
+        let mut list = List::from([1, 2]);
+        list.odd_even_separate();
+        assert_eq!(list, [1, 2]);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    #[test]
+    #[should_panic(expected = "list invariant violated: cached length is 4 but the list has 3 nodes")]
+    fn selection_sort_checks_invariants() {
+        // The sort relinks every node, and the self-check at its end notices a broken list:
+        let mut list = List::from([3, 1, 2]);
+        list.corrupt_len(4);
+        list.selection_sort();
+    }
+
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    #[test]
+    fn invariants_catch_a_cycle() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut list = List::from([1, 2, 3, 4, 5]);
+        list.corrupt_into_cycle();
+        let result = catch_unwind(AssertUnwindSafe(|| list.assert_invariants()));
+        let message = result.unwrap_err();
+        assert_eq!(message.downcast_ref::<&str>(), Some(&"list invariant violated: the nodes form a cycle"));
+
+        // Dropping it would go round forever, so cut the loop again first (after the 5th node):
+        *list.link_at_mut(5) = None;
+        list.assert_invariants();
+    }
+
+    // Variance: these only have to compile. A List<&'static str> (or an iterator over one) can
+    // be used where a shorter lifetime is expected, because List is covariant in T. (The doc test
+    // on List::_marker shows the invariant version failing to compile.)
+    fn shorten_list<'a>(list: List<&'static str>) -> List<&'a str> {
+        list
+    }
+
+    fn shorten_iter<'i, 'a>(iter: super::Iter<'i, &'static str>) -> super::Iter<'i, &'a str> {
+        iter
+    }
+
+    fn shorten_into_iter<'a>(iter: super::IntoIter<&'static str>) -> super::IntoIter<&'a str> {
+        iter
+    }
+
+    #[test]
+    fn get_and_index() {
+        let mut list: List<i32> = List::new();
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.get_mut(0), None);
+
+        list.push(7);
+        assert_eq!(list.get(0), Some(&7));
+        assert_eq!(list.get(1), None);
+
+        let mut list = List::from([1, 2, 3, 4]);
+        let len = list.len();
+        assert_eq!(list.get(len - 1), Some(&4));
+        assert_eq!(list.get(len), None);
+        assert_eq!(list.get(usize::MAX), None);
+
+        *list.get_mut(1).unwrap() = 20;
+        list[2] *= 10;
+        assert_eq!(list[1], 20);
+        assert_eq!(list, [1, 20, 30, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 3 but the index is 3")]
+    fn index_out_of_bounds() {
+        let list = List::from([1, 2, 3]);
+        let _ = list[3];
+    }
+
+    #[test]
+    fn to_dot() {
+        assert_eq!(List::<i32>::new().to_dot(), "digraph L { head -> null; }");
+
+        let dot = List::from([1, 2, 3]).to_dot();
+        assert_eq!(
+            dot,
+            "digraph L {\n    head [shape=plaintext];\n    node [shape=box];\n    n0 [label=\"1\"];\n    n1 [label=\"2\"];\n    n2 [label=\"3\"];\n    head -> n0;\n    n0 -> n1;\n    n1 -> n2;\n}"
+        );
+        // Three node definitions, and besides the head's arrow two edges between them:
+        assert_eq!(dot.matches("[label=").count(), 3);
+        assert_eq!(dot.matches(" -> n").count() - 1, 2);
+        assert!(dot.starts_with("digraph L {") && dot.ends_with('}'));
+
+        // Debug output of strings is quoted, those quotes get escaped:
+        assert!(List::from(["a \"b\""]).to_dot().contains(r#"n0 [label="\"a \\\"b\\\"\""];"#));
+    }
+
+    #[test]
+    fn push_back() {
+        let mut list = List::new();
+        list.push_back(2);
+        list.push_back(3);
+        list.push(1);
+        list.push_back(4);
+        assert_eq!(list, [1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn write_to_a_char_list() {
+        use core::fmt::Write;
+
+        let mut list = List::from(['>', ' ']);
+        let name = "Ferris";
+        write!(list, "hello {}!", name).unwrap();
+        assert_eq!(list.iter().collect::<String>(), "> hello Ferris!");
+        assert_eq!(list.len(), 15);
+
+        // Nothing written, nothing changed:
+        write!(list, "").unwrap();
+        assert_eq!(list.len(), 15);
+
+        let mut list = List::new();
+        writeln!(list, "one\ntwo").unwrap();
+        write!(list, "{}\r\n", 3).unwrap();
+        assert_eq!(list, ['o', 'n', 'e', '\n', 't', 'w', 'o', '\n', '3', '\r', '\n']);
+    }
+
+    #[test]
+    fn collect_string() {
+        let text = String::from("linked lists!");
+        let list: List<char> = text.chars().collect();
+        assert_eq!(list.collect_string(), text);
+
+        assert_eq!(List::new().collect_string(), "");
+
+        let list = List::from(['h', 'i', ' ', '\u{1F600}', 'é']);
+        let string = list.collect_string();
+        assert_eq!(string, "hi \u{1F600}é");
+        assert_eq!(string.len(), 9);
+    }
+
+    #[test]
+    fn take_n() {
+        let list = || List::from([1, 2, 3, 4, 5, 6]);
+
+        let (front, rest) = list().take_n(0);
+        assert!(front.is_empty());
+        assert_eq!(rest, [1, 2, 3, 4, 5, 6]);
+
+        let (front, rest) = list().take_n(3);
+        assert_eq!((front.len(), rest.len()), (3, 3));
+        assert_eq!(front, [1, 2, 3]);
+        assert_eq!(rest, [4, 5, 6]);
+
+        let (front, rest) = list().take_n(6);
+        assert_eq!(front, [1, 2, 3, 4, 5, 6]);
+        assert!(rest.is_empty());
+
+        let (front, rest) = list().take_n(7);
+        assert_eq!(front, [1, 2, 3, 4, 5, 6]);
+        assert!(rest.is_empty());
+
+        // The nodes are moved, not copied:
+        let (measured, allocated) = crate::alloc_counter::measure(list);
+        let ((front, rest), split) = crate::alloc_counter::measure(|| measured.take_n(2));
+        assert_eq!(allocated.allocations, 6);
+        assert_eq!(split.allocations, 0);
+        assert_eq!((front.len(), rest.len()), (2, 4));
+    }
+
+    #[test]
+    fn skip_n() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut list = recorders(&log, 5);
+
+        list.skip_n(2);
+        assert_eq!(*log.borrow(), [0, 1]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek().map(|r| r.0), Some(2));
+
+        list.skip_n(0);
+        assert_eq!(list.len(), 3);
+
+        list.skip_n(10);
+        assert_eq!(*log.borrow(), [0, 1, 2, 3, 4]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn cursor_edits() {
+        let mut list = List::from([1, 2, 3]);
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.current(), Some(&1));
+        cursor.insert_before(0);
+        assert_eq!((cursor.index(), cursor.current()), (1, Some(&1)));
+        assert!(cursor.move_next());
+        *cursor.current_mut().unwrap() *= 10;
+        cursor.insert_after(25);
+        assert_eq!(cursor.remove_current(), Some(20));
+        assert_eq!(cursor.current(), Some(&25));
+        assert!(cursor.move_next() && cursor.move_next());
+        assert!(!cursor.move_next());
+        assert_eq!((cursor.index(), cursor.current()), (4, None));
+        assert_eq!(cursor.remove_current(), None);
+        cursor.insert_after(4);
+        cursor.insert_before(5);
+        assert_eq!(cursor.index(), 6);
+        assert_eq!(list, [0, 1, 25, 3, 4, 5]);
+        assert_eq!(list.len(), 6);
+        assert_eq!(list.iter().count(), list.len());
+
+        let mut empty: List<i32> = List::new();
+        let mut cursor = empty.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(1);
+        assert_eq!(empty, [1]);
+    }
+
+    #[test]
+    fn cursor_splice() {
+        let mut list = List::from([1, 2, 3, 4, 5]);
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.splice_before(List::from([10, 11, 12]));
+        assert_eq!((cursor.index(), cursor.current()), (5, Some(&3)));
+        assert_eq!(list, [1, 2, 10, 11, 12, 3, 4, 5]);
+        assert_eq!(list.len(), 8);
+
+        let mut list = List::from([1, 2, 3, 4, 5]);
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.splice_after(List::from([10, 11, 12]));
+        assert_eq!((cursor.index(), cursor.current()), (2, Some(&3)));
+        assert!(cursor.move_next());
+        assert_eq!(cursor.current(), Some(&10));
+        assert_eq!(list, [1, 2, 3, 10, 11, 12, 4, 5]);
+        assert_eq!(list.len(), 8);
+
+        // At either end, and with nothing to splice:
+        let mut list = List::from([2]);
+        let mut cursor = list.cursor_mut();
+        cursor.splice_before(List::from([0, 1]));
+        cursor.splice_after(List::new());
+        cursor.move_next();
+        cursor.splice_after(List::from([3]));
+        cursor.splice_before(List::from([4]));
+        cursor.splice_before(List::new());
+        assert_eq!(cursor.index(), 5);
+        assert_eq!(list, [0, 1, 2, 3, 4]);
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn copy_within() {
+        let digits = || (0..10).collect::<List<i32>>();
+
+        // Non-overlapping, forwards and backwards:
+        let mut list = digits();
+        list.copy_within(0, 3, 7);
+        assert_eq!(list, [0, 1, 2, 3, 4, 5, 6, 0, 1, 2]);
+        let mut list = digits();
+        list.copy_within(8, 10, 1);
+        assert_eq!(list, [0, 8, 9, 3, 4, 5, 6, 7, 8, 9]);
+
+        // Overlapping, both ways, against slice::copy_within:
+        for (start, end, dst) in [(0, 5, 3), (3, 8, 1), (2, 9, 2), (4, 4, 10), (0, 10, 0)] {
+            let mut list = digits();
+            let mut vec: Vec<i32> = (0..10).collect();
+            list.copy_within(start, end, dst);
+            vec.copy_within(start..end, dst);
+            assert_eq!(list, vec);
+            assert_eq!(list.len(), 10);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 11 is out of range for a list of length 10")]
+    fn copy_within_source_out_of_bounds() {
+        (0..10).collect::<List<i32>>().copy_within(5, 11, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start index 6 is greater than end index 5")]
+    fn copy_within_backwards_source() {
+        (0..10).collect::<List<i32>>().copy_within(6, 5, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "destination index 8 has no room for 3 elements in a list of length 10")]
+    fn copy_within_destination_out_of_bounds() {
+        (0..10).collect::<List<i32>>().copy_within(0, 3, 8);
+    }
+
+    #[test]
+    fn borrowed_tail() {
+        let list = List::from([1, 2, 3, 4]);
+        let tail = list.borrowed_tail();
+        // Taken again, the tail is the same view on the same nodes:
+        let again = list.borrowed_tail();
+        assert_eq!(tail.iter().collect::<Vec<_>>(), [&2, &3, &4]);
+        assert_eq!(again.iter().collect::<Vec<_>>(), [&2, &3, &4]);
+        assert!(std::ptr::eq(tail.head().unwrap(), again.head().unwrap()));
+        assert!(std::ptr::eq(tail.head().unwrap(), list.iter().nth(1).unwrap()));
+        assert_eq!(tail.len(), 3);
+
+        // cdr all the way down:
+        let rest = tail.borrowed_tail().borrowed_tail();
+        assert_eq!(format!("{:?}", rest), "[4]");
+        assert_eq!(rest.len(), 1);
+        let empty = rest.borrowed_tail();
+        assert!(empty.is_empty());
+        assert_eq!(empty.head(), None);
+        assert_eq!(empty.borrowed_tail().len(), 0);
+        assert!(List::<i32>::new().borrowed_tail().is_empty());
+
+        // A view is Copy, and still usable after being iterated:
+        let sum: i32 = tail.into_iter().sum();
+        assert_eq!(sum, 9);
+        assert_eq!(list.as_list_ref().len(), 4);
+        assert_eq!(tail.head(), Some(&2));
+    }
+
+    #[test]
+    fn unfold() {
+        let countdown = List::unfold(5, |n| if n > 0 { Some((n, n - 1)) } else { None });
+        assert_eq!(countdown, [5, 4, 3, 2, 1]);
+
+        // Fibonacci numbers up to 100, with the pair of the last two as the state:
+        let fib = List::unfold((0u32, 1u32), |(a, b)| if a <= 100 { Some((a, (b, a + b))) } else { None });
+        assert_eq!(fib, [0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89]);
+
+        let empty = List::<i32>::unfold((), |()| None);
+        assert!(empty.is_empty());
+
+        // FnMut, and the state can be anything, here an iterator:
+        let mut calls = 0;
+        let words = List::unfold("a b c".split(' '), |mut words| {
+            calls += 1;
+            Some((words.next()?.to_uppercase(), words))
+        });
+        assert_eq!(words, ["A", "B", "C"]);
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn from_sorted() {
+        let list = List::from_sorted(&[1, 3, 5, 7, 9]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 3, 5, 7, 9]);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.peek(), Some(&1));
+
+        assert!(List::<i32>::from_sorted(&[]).is_empty());
+        assert_eq!(List::from_sorted(&["only"]), ["only"]);
+
+        // Every length up to a few powers of two, so both halves get to be empty or odd:
+        for n in 0..40 {
+            let sorted: Vec<String> = (0..n).map(|i| format!("{:02}", i)).collect();
+            let list = List::from_sorted(&sorted);
+            assert_eq!(list.len(), n);
+            assert!(list.iter().eq(sorted.iter()));
+        }
+    }
+
+    #[test]
+    fn from_range_step() {
+        assert_eq!(List::from_range_step(0, 10, 2), [0, 2, 4, 6, 8]);
+        assert_eq!(List::from_range_step(0, 9, 3), [0, 3, 6]);
+        assert_eq!(List::from_range_step(10, 0, -3), [10, 7, 4, 1]);
+        assert_eq!(List::from_range_step(5u8, 6, 100), [5]);
+        assert_eq!(List::from_range_step(0, 10, 2).len(), 5);
+
+        // Empty when start is already at (or past) the end:
+        assert!(List::from_range_step(3, 3, 1).is_empty());
+        assert!(List::from_range_step(3, 3, -1).is_empty());
+        assert!(List::from_range_step(5, 0, 1).is_empty());
+        assert!(List::from_range_step(0, 5, -1).is_empty());
+
+        // Steps that span almost the whole type, stopping right before the edge:
+        let step = i64::MAX / 2;
+        let big = List::from_range_step(i64::MIN, step, step);
+        assert_eq!(big, [i64::MIN, i64::MIN + step, -2, step - 2]);
+
+        // Floats work, with the usual rounding: ten steps of 0.1 add up to slightly less than
+        // 1.0, so 1.0 - ε is still in the range.
+        let tenths = List::from_range_step(0.0, 1.0, 0.1);
+        assert_eq!(tenths.len(), 11);
+        assert_eq!(tenths.get(3), Some(&0.30000000000000004));
+        assert!(tenths.iter().last().is_some_and(|&last| last < 1.0 && 1.0 - last < 1e-9));
+        assert_eq!(List::from_range_step(1.0, 0.0, -0.25), [1.0, 0.75, 0.5, 0.25]);
+        // A NaN step has no direction (from_range_step_nan checks the message):
+        assert!(std::panic::catch_unwind(|| List::from_range_step(0.0, 1.0, f64::NAN)).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "from_range_step: step must not be zero")]
+    fn from_range_step_zero() {
+        List::from_range_step(0, 10, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "from_range_step: step must not be NaN")]
+    fn from_range_step_nan() {
+        List::from_range_step(0.0, 1.0, f64::NAN);
+    }
+
+    // The next step would go past u8::MAX, but it's never taken, in debug or release builds.
+    #[test]
+    fn from_range_step_overflow() {
+        assert_eq!(List::from_range_step(250u8, 255, 3), [250, 253]);
+        assert_eq!(List::from_range_step(250u8, 255, 10), [250]);
+        assert_eq!(List::from_range_step(-120i8, -128, -5), [-120, -125]);
+        assert_eq!(List::from_range_step(i8::MIN, i8::MAX, 100), [-128, -28, 72]);
+    }
+
+    #[test]
+    fn flatten_options() {
+        let list = List::from([Some(1), None, Some(3), None, Some(5)]);
+        let flat = list.flatten_options();
+        assert_eq!(flat, [1, 3, 5]);
+        assert_eq!(flat.len(), 3);
+
+        assert!(List::from([None::<i32>, None, None]).flatten_options().is_empty());
+        assert_eq!(List::from([Some('a'), Some('b'), Some('c')]).flatten_options(), ['a', 'b', 'c']);
+        assert!(List::<Option<i32>>::new().flatten_options().is_empty());
+    }
+
+    #[test]
+    fn lists_move_between_threads() {
+        use std::thread;
+
+        // Send: the whole list moves into the thread and comes back.
+        let list = List::from([1, 2, 3]);
+        let list = thread::spawn(move || {
+            let mut list = list;
+            list.push(0);
+            list
+        })
+        .join()
+        .unwrap();
+        assert_eq!(list, [0, 1, 2, 3]);
+
+        // Sync: several threads read the same list at once.
+        let sums: Vec<i32> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..3).map(|_| scope.spawn(|| list.iter().sum::<i32>())).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        assert_eq!(sums, [6, 6, 6]);
+    }
+
+    #[test]
+    fn covariance() {
+        let local = String::from("local");
+        // A list of &'static str, and one that borrows `local`, can be mixed:
+        let mut list = shorten_list(List::from(["static"]));
+        list.push(&local);
+        assert_eq!(list, ["local", "static"]);
+
+        let statics = List::from(["a", "b"]);
+        let mut iter = shorten_iter(statics.iter());
+        assert_eq!(iter.next(), Some(&"a"));
+        let mut iter = shorten_into_iter(statics.into_iter());
+        assert_eq!(iter.next(), Some("a"));
     }
 }
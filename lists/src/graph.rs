@@ -0,0 +1,272 @@
+// An undirected graph stored as adjacency lists: for every vertex, a List of its neighbours.
+//
+// The vertices are the numbers 0..num_vertices, so the lists can live in a Vec indexed by vertex.
+// An edge u-v is in two lists, v in u's and u in v's (a loop v-v only once, in v's own list).
+//
+// Compared to an adjacency matrix (a num_vertices x num_vertices grid of bools) this takes space
+// for the edges that exist instead of all that could, which is what you want for sparse graphs.
+// The price is that has_edge has to walk a list, O(degree) instead of O(1).
+//
+//   0 - 1        adjacency[0] = [2, 1]
+//   |   |        adjacency[1] = [3, 0]
+//   2   3        adjacency[2] = [0]
+//                adjacency[3] = [1]
+
+use crate::generic_lists::List;
+use crate::queue::Queue;
+use crate::stack::Stack;
+
+pub struct Graph {
+    adjacency: Vec<List<usize>>,
+    num_vertices: usize,
+}
+
+impl Graph {
+    // A graph with vertices 0..num_vertices and no edges.
+    pub fn new(num_vertices: usize) -> Self {
+        Graph { adjacency: (0..num_vertices).map(|_| List::new()).collect(), num_vertices }
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.num_vertices
+    }
+
+    // Connects u and v. An edge that's already there isn't added a second time (this is a
+    // simple graph, not a multigraph), which costs a walk over u's neighbours. Panics if u or v
+    // isn't a vertex.
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        if self.has_edge(u, v) {
+            return;
+        }
+        self.check_vertex(v);
+        // New neighbours go in front, so a list has the most recently added first.
+        self.adjacency[u].push(v);
+        if u != v {
+            self.adjacency[v].push(u);
+        }
+    }
+
+    // The vertices connected to v, most recently added first. Panics if v isn't a vertex.
+    pub fn neighbors(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.check_vertex(v);
+        self.adjacency[v].iter().copied()
+    }
+
+    // O(degree of u). Panics if u or v isn't a vertex.
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.check_vertex(v);
+        self.neighbors(u).any(|n| n == v)
+    }
+
+    // The number of edges at v, O(1) since the List keeps its length. (A loop counts once here,
+    // where graph theory would count it twice.) Panics if v isn't a vertex.
+    pub fn degree(&self, v: usize) -> usize {
+        self.check_vertex(v);
+        self.adjacency[v].len()
+    }
+
+    // Breadth-first search: the vertices reachable from `start` in the order they are visited,
+    // closest first (start, then its neighbours, then theirs, ...). Vertices that can't be
+    // reached from start aren't in it. Panics if start isn't a vertex.
+    //
+    // The vertices still to visit wait in a queue, so they come out in the order they were found.
+    // A vertex is marked when it goes in, so it goes in only once even if it is found again
+    // through a cycle before it is visited.
+    pub fn bfs(&self, start: usize) -> List<usize> {
+        self.check_vertex(start);
+        let mut seen = vec![false; self.num_vertices];
+        let mut order = Vec::new();
+        let mut waiting = Queue::new();
+        seen[start] = true;
+        waiting.push(start);
+        while let Some(v) = waiting.pop() {
+            order.push(v);
+            for n in self.neighbors(v) {
+                if !seen[n] {
+                    seen[n] = true;
+                    waiting.push(n);
+                }
+            }
+        }
+        order.into_iter().collect()
+    }
+
+    // Depth-first search: the same vertices, but each path is followed as far as it goes before
+    // backing up to try the next one. Panics if start isn't a vertex.
+    //
+    // The same loop as bfs with a stack in place of the queue, so the vertex found last is
+    // visited first. Here a vertex can be pushed more than once (by every neighbour that finds
+    // it before it's visited), and is only marked when it comes off the stack; marking on the way
+    // in would skip the deeper path to it, and the order would no longer be depth first. Of the
+    // neighbours of a vertex, the one neighbors() gives last is explored first.
+    pub fn dfs(&self, start: usize) -> List<usize> {
+        self.check_vertex(start);
+        let mut visited = vec![false; self.num_vertices];
+        let mut order = Vec::new();
+        let mut waiting = Stack::new();
+        waiting.push(start);
+        while let Some(v) = waiting.pop() {
+            if visited[v] {
+                continue;
+            }
+            visited[v] = true;
+            order.push(v);
+            for n in self.neighbors(v).filter(|&n| !visited[n]) {
+                waiting.push(n);
+            }
+        }
+        order.into_iter().collect()
+    }
+
+    fn check_vertex(&self, v: usize) {
+        assert!(
+            v < self.num_vertices,
+            "vertex {} is out of range for a graph with {} vertices",
+            v,
+            self.num_vertices
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Graph;
+
+    // A house: a square 0-1-2-3 with a roof 4 on top of 0 and 1.
+    //
+    //     4
+    //    / \
+    //   0 - 1
+    //   |   |
+    //   3 - 2
+    fn house() -> Graph {
+        let mut graph = Graph::new(5);
+        for &(u, v) in &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 4), (1, 4)] {
+            graph.add_edge(u, v);
+        }
+        graph
+    }
+
+    const HOUSE_MATRIX: [[bool; 5]; 5] = [
+        [false, true, false, true, true],
+        [true, false, true, false, true],
+        [false, true, false, true, false],
+        [true, false, true, false, false],
+        [true, true, false, false, false],
+    ];
+
+    #[test]
+    fn edges_and_degrees() {
+        let graph = house();
+        assert_eq!(graph.num_vertices(), 5);
+        assert!(graph.has_edge(0, 1));
+        assert!(graph.has_edge(1, 0));
+        assert!(graph.has_edge(4, 1));
+        assert!(!graph.has_edge(0, 2));
+        assert!(!graph.has_edge(4, 4));
+
+        let degrees: Vec<usize> = (0..5).map(|v| graph.degree(v)).collect();
+        assert_eq!(degrees, [3, 3, 2, 2, 2]);
+        // Every edge is counted at both of its ends:
+        assert_eq!(degrees.iter().sum::<usize>(), 2 * 6);
+
+        // Most recently added first:
+        assert_eq!(graph.neighbors(0).collect::<Vec<_>>(), [4, 3, 1]);
+    }
+
+    #[test]
+    fn matches_the_adjacency_matrix() {
+        let graph = house();
+        for (u, row) in HOUSE_MATRIX.iter().enumerate() {
+            for (v, &connected) in row.iter().enumerate() {
+                assert_eq!(graph.has_edge(u, v), connected, "edge {}-{}", u, v);
+            }
+            let mut neighbors: Vec<usize> = graph.neighbors(u).collect();
+            neighbors.sort_unstable();
+            let from_matrix: Vec<usize> = (0..5).filter(|&v| row[v]).collect();
+            assert_eq!(neighbors, from_matrix);
+            assert_eq!(graph.degree(u), from_matrix.len());
+        }
+    }
+
+    #[test]
+    fn repeated_edges_and_loops() {
+        let mut graph = Graph::new(3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 0);
+        graph.add_edge(0, 1);
+        assert_eq!(graph.degree(0), 1);
+        assert_eq!(graph.degree(1), 1);
+
+        graph.add_edge(2, 2);
+        assert!(graph.has_edge(2, 2));
+        assert_eq!(graph.neighbors(2).collect::<Vec<_>>(), [2]);
+        assert_eq!(graph.degree(2), 1);
+        assert_eq!(Graph::new(0).num_vertices(), 0);
+    }
+
+    fn graph(num_vertices: usize, edges: &[(usize, usize)]) -> Graph {
+        let mut graph = Graph::new(num_vertices);
+        for &(u, v) in edges {
+            graph.add_edge(u, v);
+        }
+        graph
+    }
+
+    #[test]
+    fn traversals_of_a_path() {
+        // 0 - 1 - 2 - 3 - 4, and 1 - 4 as a shortcut.
+        let graph = graph(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (1, 4)]);
+        // Neighbours of 1 are [4, 2, 0] (newest first), so breadth first finds 4 before 2:
+        assert_eq!(graph.bfs(0), [0, 1, 4, 2, 3]);
+        // Depth first goes down 0-1-2-3-4 before it would look at the shortcut:
+        assert_eq!(graph.dfs(0), [0, 1, 2, 3, 4]);
+        assert_eq!(graph.bfs(2), [2, 3, 1, 4, 0]);
+        assert_eq!(graph.dfs(2), [2, 1, 0, 4, 3]);
+    }
+
+    #[test]
+    fn traversals_stay_in_their_component() {
+        // Two pieces: 0 - 1 - 2 and 3 - 4.
+        let graph = graph(5, &[(0, 1), (1, 2), (3, 4)]);
+        assert_eq!(graph.bfs(1), [1, 2, 0]);
+        assert_eq!(graph.dfs(0), [0, 1, 2]);
+        assert_eq!(graph.bfs(4), [4, 3]);
+        assert_eq!(graph.dfs(3), [3, 4]);
+
+        // A vertex with no edges at all:
+        let lonely = Graph::new(3);
+        assert_eq!(lonely.bfs(2), [2]);
+        assert_eq!(lonely.dfs(2), [2]);
+    }
+
+    #[test]
+    fn traversals_with_cycles() {
+        // The house has two cycles (the square and the roof triangle); every vertex is still
+        // visited exactly once.
+        let house = house();
+        for start in 0..5 {
+            for order in [house.bfs(start), house.dfs(start)] {
+                let mut vertices: Vec<usize> = order.iter().copied().collect();
+                assert_eq!(vertices[0], start);
+                vertices.sort_unstable();
+                assert_eq!(vertices, [0, 1, 2, 3, 4]);
+            }
+        }
+        // From the roof, breadth first has both of 0 and 1 before the bottom floor:
+        assert_eq!(house.bfs(4), [4, 1, 0, 2, 3]);
+        // Depth first goes down to 0 and then all the way round the square:
+        assert_eq!(house.dfs(4), [4, 0, 1, 2, 3]);
+
+        // A loop on the start vertex doesn't count it twice:
+        let looped = graph(2, &[(0, 0), (0, 1)]);
+        assert_eq!(looped.bfs(0), [0, 1]);
+        assert_eq!(looped.dfs(0), [0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "vertex 5 is out of range for a graph with 5 vertices")]
+    fn vertex_out_of_range() {
+        house().add_edge(0, 5);
+    }
+}
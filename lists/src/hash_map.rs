@@ -0,0 +1,188 @@
+// A hash table with separate chaining: a Vec of buckets, each one a List of (key, value) pairs.
+//
+// A key's hash picks its bucket (hash % number of buckets). Different keys can land in the same
+// bucket, and chaining simply keeps all of them there in a list that lookups walk, comparing keys:
+//
+//   buckets[0] = [("b", 2)]
+//   buckets[1] = []
+//   buckets[2] = [("e", 5), ("a", 1)]     <- "a" and "e" collided
+//   buckets[3] = [("c", 3)]
+//
+// A lookup costs O(length of one bucket). On average that's the load factor, entries / buckets,
+// so the table keeps it low: when an insert pushes it over 0.75 the number of buckets doubles and
+// every entry is moved to the bucket its hash picks in the bigger table. Doubling means each entry
+// is moved O(1) times on average over all the inserts, so inserts stay amortized O(1).
+//
+// std's HashMap does open addressing instead (every entry right in one array, no node to follow
+// per step), which is a lot kinder to the cache; the benchmarks compare the two.
+
+use crate::generic_lists::List;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+const INITIAL_BUCKETS: usize = 8;
+
+pub struct HashMap<K, V> {
+    buckets: Vec<List<(K, V)>>,
+    len: usize,
+    // Like std's, the hash function is seeded randomly per map, so nobody can pick keys that all
+    // land in one bucket ahead of time.
+    hasher: RandomState,
+}
+
+impl<K: Hash + Eq, V> HashMap<K, V> {
+    // An empty map. It already has a few (empty) buckets, so the first inserts don't resize.
+    pub fn new() -> Self {
+        HashMap { buckets: empty_buckets(INITIAL_BUCKETS), len: 0, hasher: RandomState::new() }
+    }
+
+    // Inserts value for key. If the key was already there its value is replaced, and the old
+    // one returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let bucket = self.bucket(&key);
+        if let Some((_, old)) = self.buckets[bucket].iter_mut().find(|(k, _)| *k == key) {
+            return Some(mem::replace(old, value));
+        }
+        self.buckets[bucket].push((key, value));
+        self.len += 1;
+        // Over 0.75 entries per bucket: len / buckets > 3 / 4, without the division.
+        if self.len * 4 > self.buckets.len() * 3 {
+            self.grow();
+        }
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.buckets[self.bucket(key)].iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Takes the key out of the map and returns its value. The bucket is walked with a cursor, so
+    // the entry is unlinked where it's found instead of walking to it a second time by index.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let bucket = self.bucket(key);
+        let mut cursor = self.buckets[bucket].cursor_mut();
+        loop {
+            match cursor.current() {
+                Some((k, _)) if k == key => break,
+                Some(_) => cursor.move_next(),
+                None => return None,
+            };
+        }
+        let (_, value) = cursor.remove_current()?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn bucket(&self, key: &K) -> usize {
+        (self.hasher.hash_one(key) % self.buckets.len() as u64) as usize
+    }
+
+    // Twice as many buckets, and every entry moved to its bucket in the new table. The pairs
+    // move, nothing is cloned; only the hashes are computed again.
+    fn grow(&mut self) {
+        let bigger = empty_buckets(self.buckets.len() * 2);
+        let old = mem::replace(&mut self.buckets, bigger);
+        for mut list in old {
+            while let Some((key, value)) = list.pop() {
+                let bucket = self.bucket(&key);
+                self.buckets[bucket].push((key, value));
+            }
+        }
+    }
+}
+
+fn empty_buckets<K, V>(n: usize) -> Vec<List<(K, V)>> {
+    (0..n).map(|_| List::new()).collect()
+}
+
+impl<K: Hash + Eq, V> Default for HashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HashMap;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = HashMap::default();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&"a"), None);
+        assert_eq!(map.remove(&"a"), None);
+
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("b", 2), None);
+        assert_eq!(map.get(&"a"), Some(&1));
+        assert!(map.contains_key(&"b"));
+        assert!(!map.contains_key(&"c"));
+
+        // Inserting an existing key replaces the value and doesn't add an entry:
+        assert_eq!(map.insert("a", 10), Some(1));
+        assert_eq!(map.get(&"a"), Some(&10));
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.remove(&"a"), Some(10));
+        assert_eq!(map.remove(&"a"), None);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&"b"), Some(&2));
+    }
+
+    #[test]
+    fn many_entries() {
+        let n = if cfg!(miri) { 200 } else { 10_000 };
+        let mut map = HashMap::new();
+        for i in 0..n {
+            assert_eq!(map.insert(i, i.to_string()), None);
+        }
+        assert_eq!(map.len(), n);
+        // It grew along the way and kept the load factor at or below 0.75:
+        assert!(map.buckets.len() * 3 >= map.len() * 4);
+        for i in 0..n {
+            assert_eq!(map.get(&i), Some(&i.to_string()));
+        }
+
+        // Remove the even half:
+        for i in (0..n).step_by(2) {
+            assert_eq!(map.remove(&i), Some(i.to_string()));
+        }
+        assert_eq!(map.len(), n / 2);
+        for i in 0..n {
+            assert_eq!(map.contains_key(&i), i % 2 == 1);
+        }
+        let entries: usize = map.buckets.iter().map(|bucket| bucket.len()).sum();
+        assert_eq!(entries, map.len());
+    }
+
+    #[test]
+    fn colliding_keys() {
+        // With a single bucket every key collides, so this checks the chaining itself.
+        let mut map = HashMap::new();
+        map.buckets.truncate(1);
+        for key in ["x", "y", "z"] {
+            map.buckets[0].push((key, key.len()));
+            map.len += 1;
+        }
+        assert_eq!(map.get(&"y"), Some(&1));
+        // Removing from the middle of the chain leaves the rest:
+        assert_eq!(map.remove(&"y"), Some(1));
+        assert_eq!(map.get(&"x"), Some(&1));
+        assert_eq!(map.get(&"z"), Some(&1));
+        assert_eq!(map.get(&"y"), None);
+        assert_eq!(map.buckets[0].len(), 2);
+    }
+}
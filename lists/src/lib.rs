@@ -1,7 +1,84 @@
+// The list types only need a heap allocator, so with the default "std" feature turned off the
+// crate is #![no_std] and builds for bare-metal targets that provide `alloc`, e.g.
+//   cargo build --lib --no-default-features --target thumbv7em-none-eabi
+// Only generic_lists (and its error type) is available then; the other modules are still written
+// against std and need the "std" feature. Tests always build with std.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+extern crate alloc;
+
+// first, second and iterators are the tutorial's code as it was written, notes and all, so they
+// keep a few patterns clippy would rewrite (`elem: elem`, `.as_ref().map(|node| &**node)`, ...).
+#[cfg(feature = "std")]
+#[allow(clippy::redundant_field_names, clippy::new_without_default)]
 pub mod first;
+#[cfg(feature = "std")]
+#[allow(clippy::redundant_field_names, clippy::new_without_default)]
 pub mod second;
+#[cfg(feature = "std")]
+pub mod third;
+#[cfg(feature = "std")]
+pub mod fourth;
+#[cfg(feature = "std")]
+pub mod fifth;
 pub mod generic_lists;
+#[cfg(feature = "std")]
+#[allow(
+    clippy::redundant_field_names,
+    clippy::new_without_default,
+    clippy::should_implement_trait,
+    clippy::option_as_ref_deref,
+    clippy::option_map_unit_fn
+)]
 pub mod iterators;
+#[cfg(feature = "std")]
+pub mod bigint;
+pub mod errors;
+#[cfg(feature = "std")]
+pub mod circular_list;
+#[cfg(feature = "std")]
+pub mod algorithms;
+#[cfg(feature = "std")]
+pub mod polynomial;
+#[cfg(feature = "std")]
+pub mod atomic_stack;
+#[cfg(feature = "std")]
+pub mod doubly_linked_list;
+#[cfg(feature = "std")]
+pub mod lru_cache;
+#[cfg(feature = "std")]
+pub mod alist;
+#[cfg(feature = "std")]
+pub mod dlist;
+#[cfg(feature = "std")]
+pub mod list_zipper;
+#[cfg(feature = "std")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod small_list;
+#[cfg(feature = "std")]
+pub mod fixed_list;
+#[cfg(feature = "std")]
+pub mod channel;
+// Keeps part of its ABA counter in the top 16 bits of 64-bit pointers, see the module.
+#[cfg(all(feature = "std", target_pointer_width = "64"))]
+pub mod lockfree;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod zipper;
+#[cfg(feature = "std")]
+pub mod stack;
+#[cfg(feature = "std")]
+pub mod queue;
+#[cfg(feature = "std")]
+pub mod rpn;
+#[cfg(feature = "std")]
+pub mod graph;
+#[cfg(feature = "std")]
+pub mod hash_map;
+#[cfg(test)]
+mod alloc_counter;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,129 @@
+// A list zipper: a position inside a list that can move and edit in O(1).
+//
+// A singly linked list is only cheap to work on at its head. The zipper "unzips" the list at the
+// current position into two lists:
+//   - `left`: the elements before the position, nearest first (so the prefix reversed);
+//   - `right`: the element in focus followed by the rest of the list.
+// Both lists have the position at their head, so moving is just popping from one and pushing onto
+// the other, and inserting or removing at the focus is a push or a pop on `right`.
+//
+//   [1, 2, 3, 4, 5] focused on 3:   left = [2, 1]   right = [3, 4, 5]
+
+use crate::generic_lists::List;
+
+pub struct ListZipper<T> {
+    left: List<T>,
+    right: List<T>,
+}
+
+impl<T> ListZipper<T> {
+    // A zipper focused on the first element of `list`.
+    pub fn new(list: List<T>) -> Self {
+        ListZipper { left: List::new(), right: list }
+    }
+
+    // The element in focus, None once we moved past the last element (or the list is empty).
+    pub fn focus(&self) -> Option<&T> {
+        self.right.peek()
+    }
+
+    // Moves the focus one element towards the end. Returns false, without moving, when there
+    // is no focused element left to step over.
+    pub fn go_right(&mut self) -> bool {
+        match self.right.pop() {
+            Some(elem) => {
+                self.left.push(elem);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Moves the focus one element towards the start. Returns false when already at the start.
+    pub fn go_left(&mut self) -> bool {
+        match self.left.pop() {
+            Some(elem) => {
+                self.right.push(elem);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Inserts `elem` at the current position: it becomes the focus and the old focus follows it.
+    pub fn insert(&mut self, elem: T) {
+        self.right.push(elem);
+    }
+
+    // Removes the element in focus; the element after it becomes the new focus.
+    pub fn remove(&mut self) -> Option<T> {
+        self.right.pop()
+    }
+
+    // Zips the list back up: the left context goes back in front of the right one.
+    pub fn to_list(self) -> List<T> {
+        let ListZipper { mut left, mut right } = self;
+        while let Some(elem) = left.pop() {
+            right.push(elem);
+        }
+        right
+    }
+}
+
+impl<T> From<List<T>> for ListZipper<T> {
+    fn from(list: List<T>) -> Self {
+        ListZipper::new(list)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ListZipper;
+    use crate::generic_lists::List;
+
+    #[test]
+    fn edit_in_the_middle() {
+        let mut zipper = ListZipper::new([1, 2, 3, 4, 5].iter().copied().collect());
+        assert_eq!(zipper.focus(), Some(&1));
+
+        // Walk to the middle:
+        assert!(zipper.go_right());
+        assert!(zipper.go_right());
+        assert_eq!(zipper.focus(), Some(&3));
+
+        // Insert in front of the focus, it becomes the focus:
+        zipper.insert(10);
+        assert_eq!(zipper.focus(), Some(&10));
+
+        // And back to the start:
+        assert!(zipper.go_left());
+        assert!(zipper.go_left());
+        assert!(!zipper.go_left());
+        assert_eq!(zipper.focus(), Some(&1));
+
+        assert_eq!(zipper.to_list(), [1, 2, 10, 3, 4, 5]);
+    }
+
+    #[test]
+    fn remove_and_ends() {
+        let mut zipper = ListZipper::from([1, 2, 3].iter().copied().collect::<List<_>>());
+
+        assert_eq!(zipper.remove(), Some(1));
+        assert_eq!(zipper.focus(), Some(&2));
+
+        // Walk off the end, where there's nothing to remove but we can still insert (append):
+        assert!(zipper.go_right());
+        assert!(zipper.go_right());
+        assert!(!zipper.go_right());
+        assert_eq!(zipper.focus(), None);
+        assert_eq!(zipper.remove(), None);
+        zipper.insert(4);
+
+        // Zipping up from anywhere restores the order:
+        assert_eq!(zipper.to_list(), [2, 3, 4]);
+
+        let mut empty: ListZipper<i32> = ListZipper::new(List::new());
+        assert!(!empty.go_right() && !empty.go_left());
+        assert!(empty.to_list().is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+// A Treiber stack: the classic lock-free stack, with tagged pointers against the ABA problem.
+//
+// Like AtomicStack (atomic_stack.rs), push and pop build or unlink a node and then swing the
+// AtomicPtr head over with compare-and-swap, retrying if another thread changed the head in the
+// meantime. Without contention the first CAS succeeds and every operation is a handful of
+// instructions; with contention some thread always makes progress (lock-free).
+//
+// The difference is how it deals with the two dangers of pop (reading a node another thread
+// already freed, and ABA). AtomicStack delays freeing popped nodes; this one never frees them
+// while the stack is alive. A popped node goes onto a second Treiber stack, the free list, and
+// push takes its nodes from there before allocating new ones. So a pointer read from `head` always
+// points at a live node, even if that node has been popped since (it is at worst reused), and the
+// memory is given back in Drop.
+//
+// Reusing nodes is exactly what makes ABA likely, though: thread 1 reads head = A and next = B,
+// meanwhile thread 2 pops A and B and pushes A again (the same node, reused). The head is A
+// again, so thread 1's CAS succeeds and makes B, which is no longer on the stack, the head. The
+// fix is to make the head value different every time it changes: we keep a counter in the bits
+// of the head pointer that are always 0, and it goes up with every successful CAS. Thread 1's
+// CAS compares pointer *and* counter, so it fails.
+//
+// Nodes are aligned to 8 bytes, so the lowest 3 bits are free, but 3 bits only count to 8: a
+// thread stalled between its load and its CAS while the head changes exactly a multiple of 8
+// times would be fooled again. So the counter also uses the top 16 bits. On 64-bit targets
+// addresses only take the low 48 bits (that's what x86-64 and AArch64 give user space, and push
+// checks it for every node it allocates), which makes a 19-bit counter: the head has to change
+// 524288 times while one thread is stalled before it wraps. That's what the module is cfg'd to
+// 64-bit targets for. (The other usual way is a double-width CAS on a (pointer, counter) pair,
+// which std doesn't have.)
+
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// The counter's low 3 bits go in the low bits of the pointer, the other 16 in the top bits.
+const LOW_BITS: u32 = 3;
+const HIGH_SHIFT: u32 = 48;
+const TAG_MASK: usize = 0xffff << HIGH_SHIFT | ((1 << LOW_BITS) - 1);
+const COUNTER_MASK: usize = (1 << (LOW_BITS + 16)) - 1;
+
+pub struct TreiberStack<T> {
+    // Tagged: a node pointer with the counter in its free low and high bits.
+    head: AtomicPtr<Node<T>>,
+    // Popped nodes waiting to be reused, tagged the same way. Their elements are gone.
+    free: AtomicPtr<Node<T>>,
+}
+
+// align(8) is what leaves the low 3 bits of every node pointer free for the tag.
+#[repr(align(8))]
+struct Node<T> {
+    // Only initialized while the node is on `head`. Nodes on the free list have no element.
+    elem: MaybeUninit<T>,
+    // Untagged. Atomic because a thread that lost a race may still read the `next` of a node
+    // that another thread is reusing, while that thread writes it.
+    next: AtomicPtr<Node<T>>,
+}
+
+fn tag<T>(tagged: *mut Node<T>) -> usize {
+    let addr = tagged.addr();
+    (addr >> HIGH_SHIFT) << LOW_BITS | (addr & ((1 << LOW_BITS) - 1))
+}
+
+fn untag<T>(tagged: *mut Node<T>) -> *mut Node<T> {
+    tagged.map_addr(|addr| addr & !TAG_MASK)
+}
+
+// `node` with the counter after `old`'s. map_addr keeps the pointer's provenance, so the result
+// can still be used to access the node once untagged.
+fn tag_after<T>(node: *mut Node<T>, old: *mut Node<T>) -> *mut Node<T> {
+    let counter = (tag(old) + 1) & COUNTER_MASK;
+    node.map_addr(|addr| addr | (counter >> LOW_BITS) << HIGH_SHIFT | (counter & ((1 << LOW_BITS) - 1)))
+}
+
+// The CAS loops for both stacks (head and free list), on raw nodes.
+
+// SAFETY: the caller owns `node`, and `top` only holds nodes that stay allocated for as long as
+// `top` is used.
+unsafe fn push_node<T>(top: &AtomicPtr<Node<T>>, node: *mut Node<T>) {
+    let mut old = top.load(Ordering::Relaxed);
+    loop {
+        (*node).next.store(untag(old), Ordering::Relaxed);
+        // Release makes the node's contents (its element, its next) visible to whoever
+        // Acquire-loads it from `top`.
+        match top.compare_exchange_weak(old, tag_after(node, old), Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(current) => old = current,
+        }
+    }
+}
+
+// SAFETY: as for push_node. The returned node belongs to the caller.
+unsafe fn pop_node<T>(top: &AtomicPtr<Node<T>>) -> Option<*mut Node<T>> {
+    let mut old = top.load(Ordering::Acquire);
+    loop {
+        let node = untag(old);
+        if node.is_null() {
+            return None;
+        }
+        // The node may have been popped (even reused) by now, but it is still allocated, so
+        // reading `next` is fine. If it did change hands, the tag has moved on and the CAS fails.
+        let next = (*node).next.load(Ordering::Relaxed);
+        match top.compare_exchange_weak(old, tag_after(next, old), Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return Some(node),
+            Err(current) => old = current,
+        }
+    }
+}
+
+// SAFETY: the stack owns its elements and only moves them in and out, like AtomicStack, so it
+// can go to another thread when T: Send.
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+
+// SAFETY: sharing the stack lets a T pushed on one thread be popped on another, which only needs
+// T: Send. Everything shared is read and written through atomics, and no node is freed before
+// Drop, which has the stack to itself.
+//
+// This does rest on one assumption: that no thread stalls between loading `head` (or `free`)
+// and its CAS for as long as it takes the other threads to change it 2^19 times. If one did, the
+// counter would have wrapped around to the same value, the CAS would succeed with a stale `next`
+// and a node could end up on both stacks. 2^19 pushes and pops is far longer than a thread is
+// ever descheduled in practice, but it is a bound, not a proof.
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        TreiberStack { head: AtomicPtr::new(ptr::null_mut()), free: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        untag(self.head.load(Ordering::Acquire)).is_null()
+    }
+
+    pub fn push(&self, elem: T) {
+        // SAFETY: nodes are only freed in Drop, and a node popped off either stack is ours alone
+        // (nobody else writes it until it is pushed again).
+        unsafe {
+            let node = match pop_node(&self.free) {
+                Some(node) => node,
+                None => {
+                    let node = Node { elem: MaybeUninit::uninit(), next: AtomicPtr::default() };
+                    let node = Box::into_raw(Box::new(node));
+                    // The counter would overwrite address bits otherwise.
+                    assert_eq!(node.addr() & TAG_MASK, 0, "node address doesn't fit in 48 bits");
+                    node
+                }
+            };
+            (*node).elem.write(elem);
+            push_node(&self.head, node);
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        // SAFETY: as in push. Our CAS unlinked the node, so we are the only one taking its
+        // element; after that it goes on the free list empty.
+        unsafe {
+            let node = pop_node(&self.head)?;
+            let elem = (*node).elem.assume_init_read();
+            push_node(&self.free, node);
+            Some(elem)
+        }
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// &mut self means no other thread can be using the stack, so this is a plain walk over both
+// chains, no CAS needed.
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        let mut node = untag(*self.head.get_mut());
+        while !node.is_null() {
+            // SAFETY: nodes on the stack were allocated by push and hold an element.
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { boxed.elem.assume_init_drop() };
+            node = *boxed.next.get_mut();
+        }
+        let mut node = untag(*self.free.get_mut());
+        while !node.is_null() {
+            // SAFETY: nodes on the free list were allocated by push, their elements are gone.
+            let mut boxed = unsafe { Box::from_raw(node) };
+            node = *boxed.next.get_mut();
+        }
+    }
+}
+
+// Only says whether the stack is empty. Printing the elements would mean reading them while
+// another thread may be popping (moving them out), which can't be done safely through &self.
+impl<T> fmt::Debug for TreiberStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreiberStack").field("is_empty", &self.is_empty()).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tag, tag_after, untag, Node, TreiberStack, COUNTER_MASK};
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let stack = TreiberStack::default();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+        assert_eq!(format!("{:?}", stack), "TreiberStack { is_empty: true, .. }");
+    }
+
+    #[test]
+    fn popped_nodes_are_reused() {
+        let stack = TreiberStack::new();
+        stack.push(String::from("a"));
+        let first = untag(stack.head.load(Ordering::Relaxed));
+        let tagged = stack.head.load(Ordering::Relaxed);
+        assert_eq!(stack.pop().as_deref(), Some("a"));
+
+        // The same node comes back, but the head value differs thanks to the tag:
+        stack.push(String::from("b"));
+        assert_eq!(untag(stack.head.load(Ordering::Relaxed)), first);
+        assert_ne!(stack.head.load(Ordering::Relaxed), tagged);
+        assert_eq!(stack.pop().as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn counter_carries_into_the_high_bits() {
+        let mut node = Node { elem: std::mem::MaybeUninit::new(0), next: Default::default() };
+        let node: *mut Node<i32> = &mut node;
+
+        // Past 7 the counter carries from the low bits into the top ones, and the pointer is
+        // still there underneath:
+        let mut tagged = node;
+        for expected in 1..=20 {
+            tagged = tag_after(node, tagged);
+            assert_eq!(tag(tagged), expected);
+            assert_eq!(untag(tagged), node);
+        }
+        assert_ne!(tagged.addr() >> 48, 0);
+
+        // Only after all 19 bits does it wrap around to 0:
+        let last = tag_after(node, node).map_addr(|addr| addr | 0xffff << 48 | 0b110);
+        assert_eq!(tag(last), COUNTER_MASK);
+        assert_eq!(tag(tag_after(node, last)), 0);
+        assert_eq!(tag_after(node, last), node);
+    }
+
+    #[test]
+    fn drop_frees_remaining_elements() {
+        let rc = Arc::new(());
+        let stack = TreiberStack::new();
+        for _ in 0..5 {
+            stack.push(rc.clone());
+        }
+        drop(stack.pop());
+        assert_eq!(Arc::strong_count(&rc), 5);
+        drop(stack);
+        assert_eq!(Arc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn producers_and_consumers() {
+        let per_producer = if cfg!(miri) { 50 } else { 10_000 };
+        let stack = Arc::new(TreiberStack::new());
+
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    for i in 0..per_producer {
+                        stack.push(p * per_producer + i);
+                    }
+                })
+            })
+            .collect();
+        // Consumers pop until they have seen their share, spinning while the stack is empty.
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::with_capacity(per_producer);
+                    while popped.len() < per_producer {
+                        match stack.pop() {
+                            Some(value) => popped.push(value),
+                            None => thread::yield_now(),
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut seen: Vec<usize> = consumers.into_iter().flat_map(|c| c.join().unwrap()).collect();
+
+        // Every pushed value came out exactly once:
+        seen.sort_unstable();
+        assert_eq!(seen, (0..4 * per_producer).collect::<Vec<_>>());
+        assert!(stack.is_empty());
+    }
+}
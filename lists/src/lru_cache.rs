@@ -0,0 +1,160 @@
+// A least-recently-used (LRU) cache: it holds at most `capacity` entries, and when a new entry
+// doesn't fit, the one that was used longest ago is evicted.
+//
+// Two structures work together:
+//   - a DoublyLinkedList of (key, value) pairs ordered by use, most recently used at the front;
+//   - a HashMap from each key to its node in that list.
+// The map finds a key's node in O(1), and because the list is doubly linked that node can be
+// moved to the front (on every get/put) or the back node evicted, also in O(1).
+
+use crate::doubly_linked_list::{DllNode, DoublyLinkedList};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub struct LruCache<K: Eq + Hash, V> {
+    map: HashMap<K, *mut DllNode<(K, V)>>,
+    order: DoublyLinkedList<(K, V)>,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash, V> LruCache<K, V> {
+    // Panics if capacity is 0 (a cache that can't hold anything isn't useful).
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be at least 1");
+        LruCache { map: HashMap::new(), order: DoublyLinkedList::new(), capacity }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Looks up `key` and marks it as the most recently used entry.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let node = *self.map.get(key)?;
+        // SAFETY: every pointer in the map is a live node of `self.order` (entries leave the map
+        // and the list together), and the returned reference borrows self.
+        unsafe {
+            self.order.move_to_front(node);
+            Some(&(*node).elem().1)
+        }
+    }
+
+    // Inserts or updates `key`, marking it as the most recently used entry. If that makes the
+    // cache go over capacity, the least recently used entry is evicted.
+    pub fn put(&mut self, key: K, value: V)
+    where
+        K: Clone,
+    {
+        if let Some(&node) = self.map.get(&key) {
+            // SAFETY: see `get`.
+            unsafe {
+                (*node).elem_mut().1 = value;
+                self.order.move_to_front(node);
+            }
+            return;
+        }
+
+        if self.order.len() == self.capacity {
+            if let Some((evicted, _)) = self.order.pop_back() {
+                self.map.remove(&evicted);
+            }
+        }
+        let node = self.order.push_front((key.clone(), value));
+        self.map.insert(key, node);
+    }
+
+    // The keys from most to least recently used.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.order.iter().map(|(key, _)| key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LruCache;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        assert_eq!(cache.keys().copied().collect::<Vec<_>>(), vec!["c", "b", "a"]);
+
+        // "a" is the oldest, so it goes first, then "b":
+        cache.put("d", 4);
+        assert_eq!(cache.get(&"a"), None);
+        cache.put("e", 5);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.keys().copied().collect::<Vec<_>>(), vec!["e", "d", "c"]);
+    }
+
+    #[test]
+    fn access_prevents_eviction() {
+        let mut cache = LruCache::new(2);
+        cache.put(1, "one");
+        cache.put(2, "two");
+
+        // Using 1 makes 2 the least recently used entry:
+        assert_eq!(cache.get(&1), Some(&"one"));
+        cache.put(3, "three");
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some(&"one"));
+
+        // Updating an existing key counts as a use too, and doesn't evict anything:
+        cache.put(3, "THREE");
+        cache.put(1, "ONE");
+        assert_eq!(cache.len(), 2);
+        cache.put(4, "four");
+        assert_eq!(cache.get(&3), None);
+        assert_eq!(cache.get(&1), Some(&"ONE"));
+    }
+
+    #[test]
+    fn leetcode_146() {
+        // ["LRUCache","put","put","get","put","get","put","get","get","get"]
+        // [[2],[1,1],[2,2],[1],[3,3],[2],[4,4],[1],[3],[4]]
+        let mut cache = LruCache::new(2);
+        cache.put(1, 1);
+        cache.put(2, 2);
+        assert_eq!(cache.get(&1), Some(&1));
+        cache.put(3, 3); // evicts key 2
+        assert_eq!(cache.get(&2), None);
+        cache.put(4, 4); // evicts key 1
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&3), Some(&3));
+        assert_eq!(cache.get(&4), Some(&4));
+
+        // ["LRUCache","put","put","put","put","get","get"] [[2],[2,1],[1,1],[2,3],[4,1],[1],[2]]
+        let mut cache = LruCache::new(2);
+        cache.put(2, 1);
+        cache.put(1, 1);
+        cache.put(2, 3);
+        cache.put(4, 1); // evicts key 1
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some(&3));
+
+        // Capacity 1: every new key evicts the previous one.
+        let mut cache = LruCache::new(1);
+        cache.put(2, 1);
+        assert_eq!(cache.get(&2), Some(&1));
+        cache.put(3, 2);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some(&2));
+    }
+
+    #[test]
+    #[should_panic(expected = "LruCache capacity must be at least 1")]
+    fn zero_capacity() {
+        LruCache::<i32, i32>::new(0);
+    }
+}
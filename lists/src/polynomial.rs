@@ -0,0 +1,147 @@
+// Polynomials stored as a list of coefficients:
+// The head of the list is the constant term, the next node is the coefficient of x, then x^2 and
+// so on. So 1 + 2x + x^2 is the list [1, 2, 1].
+//
+// Keeping the low-degree coefficients first means adding two polynomials is a simple walk down
+// both lists side by side, padding the shorter one with zeros (T::default()).
+
+use crate::generic_lists::List;
+use std::ops::{Add, Mul};
+
+pub struct Polynomial<T>(List<T>);
+
+impl<T> Polynomial<T> {
+    // The coefficients go constant term first.
+    pub fn new(coefficients: List<T>) -> Self {
+        Polynomial(coefficients)
+    }
+
+    pub fn coefficients(&self) -> &List<T> {
+        &self.0
+    }
+
+    // The degree is the highest power of x with a stored coefficient (trailing zeros count, the
+    // polynomial doesn't know what "zero" is for T). The empty polynomial has degree 0.
+    pub fn degree(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    // Evaluates the polynomial at `x` with Horner's method:
+    //   a0 + a1 x + a2 x^2 + a3 x^3 = a0 + x (a1 + x (a2 + x a3))
+    // which needs only one multiplication per coefficient. Horner works from the highest degree
+    // down, so we first push references to the coefficients onto a temporary list, which reverses
+    // them, and then fold over that.
+    pub fn evaluate(&self, x: T) -> T
+    where
+        T: Add<Output = T> + Mul<Output = T> + Clone + Default,
+    {
+        let mut highest_first = List::new();
+        for coefficient in self.0.iter() {
+            highest_first.push(coefficient);
+        }
+        highest_first
+            .into_iter()
+            .fold(T::default(), |acc, coefficient| acc * x.clone() + coefficient.clone())
+    }
+}
+
+impl<T> From<List<T>> for Polynomial<T> {
+    fn from(coefficients: List<T>) -> Self {
+        Polynomial(coefficients)
+    }
+}
+
+impl<T> Add for Polynomial<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Clone + Default,
+{
+    type Output = Polynomial<T>;
+
+    // Add the coefficients of matching powers. When one polynomial runs out of coefficients the
+    // rest of the other one is added to zero.
+    fn add(self, other: Polynomial<T>) -> Polynomial<T> {
+        let mut lhs = self.0.into_iter();
+        let mut rhs = other.0.into_iter();
+        let sum = std::iter::from_fn(|| match (lhs.next(), rhs.next()) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or_default() + b.unwrap_or_default()),
+        });
+        Polynomial(sum.collect())
+    }
+}
+
+impl<T> Mul for Polynomial<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Clone + Default,
+{
+    type Output = Polynomial<T>;
+
+    // Every term a_i x^i of one polynomial multiplies every term b_j x^j of the other, and the
+    // product a_i b_j lands on x^(i + j).
+    fn mul(self, other: Polynomial<T>) -> Polynomial<T> {
+        if self.0.is_empty() || other.0.is_empty() {
+            return Polynomial(List::new());
+        }
+
+        let mut product = vec![T::default(); self.0.len() + other.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                let term = a.clone() * b.clone();
+                product[i + j] = product[i + j].clone() + term;
+            }
+        }
+        Polynomial(product.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Polynomial;
+
+    fn poly(coefficients: &[i64]) -> Polynomial<i64> {
+        Polynomial::new(coefficients.iter().copied().collect())
+    }
+
+    #[test]
+    fn add() {
+        // (1 + 2x + x^2) + (4 - x) = 5 + x + x^2
+        let sum = poly(&[1, 2, 1]) + poly(&[4, -1]);
+        assert_eq!(*sum.coefficients(), [5, 1, 1]);
+        assert_eq!(sum.degree(), 2);
+
+        // Adding the empty polynomial changes nothing:
+        let sum = poly(&[]) + poly(&[7, 0, 3]);
+        assert_eq!(*sum.coefficients(), [7, 0, 3]);
+    }
+
+    #[test]
+    fn multiply_degree_two() {
+        // (1 + 2x + x^2)(3 + 2x^2) = 3 + 6x + 5x^2 + 4x^3 + 2x^4
+        let product = poly(&[1, 2, 1]) * poly(&[3, 0, 2]);
+        assert_eq!(*product.coefficients(), [3, 6, 5, 4, 2]);
+        assert_eq!(product.degree(), 4);
+
+        // (x + 1)^2 = x^2 + 2x + 1
+        let square = poly(&[1, 1]) * poly(&[1, 1]);
+        assert_eq!(*square.coefficients(), [1, 2, 1]);
+
+        assert!((poly(&[1, 1]) * poly(&[])).coefficients().is_empty());
+    }
+
+    #[test]
+    fn evaluate() {
+        // x^2 + 2x + 1 at x = 3 is 16:
+        assert_eq!(poly(&[1, 2, 1]).evaluate(3), 16);
+        assert_eq!(poly(&[5]).evaluate(100), 5);
+        assert_eq!(poly(&[]).evaluate(2), 0);
+
+        // Works for floats too: 0.5 + 2x^2 at x = 1.5
+        let p: Polynomial<f64> = Polynomial::new([0.5, 0.0, 2.0].iter().copied().collect());
+        assert!((p.evaluate(1.5) - 5.0).abs() < 1e-12);
+
+        // Evaluating the product equals multiplying the evaluations:
+        let (a, b) = (poly(&[1, -2, 3]), poly(&[4, 0, -1, 2]));
+        let expected = a.evaluate(-2) * b.evaluate(-2);
+        assert_eq!((a * b).evaluate(-2), expected);
+    }
+}
@@ -0,0 +1,94 @@
+// A queue (first in, first out) made of two stacks.
+//
+// A Stack gives its elements back in reverse, so pouring one stack into another reverses them
+// again, back into the order they came in. The queue pushes onto `inbox`; pop takes from
+// `outbox`, and when that runs empty it first moves everything in the inbox over:
+//
+//   push 1, 2, 3     inbox = [3, 2, 1]   outbox = []
+//   pop -> 1         inbox = []          outbox = [3, 2]   (moved, then popped)
+//   push 4           inbox = [4]         outbox = [3, 2]
+//   pop -> 2         inbox = [4]         outbox = [3]
+//
+// Every element is moved from the inbox to the outbox once, so although a single pop can take
+// O(n), n operations take O(n) in total: amortized O(1) each. (fifth.rs gets O(1) every time with
+// a raw tail pointer; this needs no unsafe code at all. The channel does the same trick with
+// whole batches.)
+
+use crate::stack::Stack;
+
+pub struct Queue<T> {
+    inbox: Stack<T>,
+    outbox: Stack<T>,
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue { inbox: Stack::new(), outbox: Stack::new() }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.inbox.push(elem);
+    }
+
+    // The oldest element.
+    pub fn pop(&mut self) -> Option<T> {
+        self.refill();
+        self.outbox.pop()
+    }
+
+    // The element pop would return next. Needs &mut self, since it may have to move the inbox
+    // over first.
+    pub fn peek(&mut self) -> Option<&T> {
+        self.refill();
+        self.outbox.peek()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inbox.len() + self.outbox.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inbox.is_empty() && self.outbox.is_empty()
+    }
+
+    // Only when the outbox is empty: moving the inbox onto elements that are still waiting would
+    // put the newer ones in front of them.
+    fn refill(&mut self) {
+        if self.outbox.is_empty() {
+            while let Some(elem) = self.inbox.pop() {
+                self.outbox.push(elem);
+            }
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+
+    #[test]
+    fn first_in_first_out() {
+        let mut queue = Queue::default();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+        assert_eq!((queue.len(), queue.peek()), (3, Some(&1)));
+        assert_eq!(queue.pop(), Some(1));
+        // Pushed while 2 and 3 are still in the outbox, so it has to wait behind them:
+        queue.push(4);
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.peek(), Some(&4));
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.len(), 0);
+    }
+}
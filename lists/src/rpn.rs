@@ -0,0 +1,118 @@
+// A calculator for expressions in reverse Polish notation (RPN), where the operator comes after
+// its operands: "3 4 +" is 3 + 4, and "3 4 + 2 *" is (3 + 4) * 2. No parentheses or precedence
+// rules are needed, the order of the tokens says it all.
+//
+// Evaluating it is a small stack machine: read the tokens left to right, push every number on a
+// stack, and for an operator pop its two operands, apply it and push the result. At the end the
+// stack must hold exactly one number, the value of the whole expression.
+
+use crate::stack::Stack;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpnError {
+    // An operator came when there weren't two numbers on the stack for it.
+    InsufficientOperands { operator: char },
+    DivisionByZero,
+    // The result doesn't fit in an i64.
+    Overflow,
+    // Neither a number nor one of + - * /.
+    InvalidToken(String),
+    // The expression didn't leave exactly one number on the stack (0 for an empty expression,
+    // more if operators were missing).
+    UnbalancedExpression { remaining: usize },
+}
+
+impl fmt::Display for RpnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpnError::InsufficientOperands { operator } => {
+                write!(f, "operator {} needs two operands", operator)
+            }
+            RpnError::DivisionByZero => write!(f, "division by zero"),
+            RpnError::Overflow => write!(f, "the result does not fit in an i64"),
+            RpnError::InvalidToken(token) => write!(f, "invalid token {:?}", token),
+            RpnError::UnbalancedExpression { remaining } => {
+                write!(f, "expected exactly one value at the end, found {}", remaining)
+            }
+        }
+    }
+}
+
+impl Error for RpnError {}
+
+// Evaluates a space-separated RPN expression of integers and + - * /. Division rounds towards
+// zero, like i64's `/`.
+pub fn evaluate_rpn(input: &str) -> Result<i64, RpnError> {
+    let mut stack: Stack<i64> = Stack::new();
+    for token in input.split_whitespace() {
+        let operator = match token {
+            "+" => '+',
+            "-" => '-',
+            "*" => '*',
+            "/" => '/',
+            _ => {
+                let number = token.parse().map_err(|_| RpnError::InvalidToken(token.to_string()))?;
+                stack.push(number);
+                continue;
+            }
+        };
+        // The right-hand operand was pushed last, so it comes off first.
+        let (rhs, lhs) = match (stack.pop(), stack.pop()) {
+            (Some(rhs), Some(lhs)) => (rhs, lhs),
+            _ => return Err(RpnError::InsufficientOperands { operator }),
+        };
+        let result = match operator {
+            '+' => lhs.checked_add(rhs),
+            '-' => lhs.checked_sub(rhs),
+            '*' => lhs.checked_mul(rhs),
+            _ if rhs == 0 => return Err(RpnError::DivisionByZero),
+            _ => lhs.checked_div(rhs),
+        };
+        stack.push(result.ok_or(RpnError::Overflow)?);
+    }
+
+    match (stack.pop(), stack.len()) {
+        (Some(value), 0) => Ok(value),
+        (None, _) => Err(RpnError::UnbalancedExpression { remaining: 0 }),
+        (Some(_), rest) => Err(RpnError::UnbalancedExpression { remaining: rest + 1 }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{evaluate_rpn, RpnError};
+
+    #[test]
+    fn evaluates() {
+        assert_eq!(evaluate_rpn("3 4 + 2 *"), Ok(14));
+        assert_eq!(evaluate_rpn("5 1 2 + 4 * + 3 -"), Ok(14));
+        assert_eq!(evaluate_rpn("42"), Ok(42));
+        // Operand order matters for - and /, and division rounds towards zero:
+        assert_eq!(evaluate_rpn("10 3 -"), Ok(7));
+        assert_eq!(evaluate_rpn("-7 2 /"), Ok(-3));
+        // Any whitespace separates tokens:
+        assert_eq!(evaluate_rpn("  2\t3\n*  "), Ok(6));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(evaluate_rpn("1 +"), Err(RpnError::InsufficientOperands { operator: '+' }));
+        assert_eq!(evaluate_rpn("*"), Err(RpnError::InsufficientOperands { operator: '*' }));
+        assert_eq!(evaluate_rpn("4 0 /"), Err(RpnError::DivisionByZero));
+        assert_eq!(evaluate_rpn("4 2 2 - /"), Err(RpnError::DivisionByZero));
+        assert_eq!(evaluate_rpn("9223372036854775807 1 +"), Err(RpnError::Overflow));
+        assert_eq!(evaluate_rpn("-9223372036854775808 -1 /"), Err(RpnError::Overflow));
+        assert_eq!(evaluate_rpn("2 x +"), Err(RpnError::InvalidToken("x".to_string())));
+        assert_eq!(evaluate_rpn("2 3 ^"), Err(RpnError::InvalidToken("^".to_string())));
+        assert_eq!(evaluate_rpn(""), Err(RpnError::UnbalancedExpression { remaining: 0 }));
+        assert_eq!(evaluate_rpn("1 2 3 +"), Err(RpnError::UnbalancedExpression { remaining: 2 }));
+
+        assert_eq!(RpnError::DivisionByZero.to_string(), "division by zero");
+        assert_eq!(
+            RpnError::UnbalancedExpression { remaining: 2 }.to_string(),
+            "expected exactly one value at the end, found 2"
+        );
+    }
+}
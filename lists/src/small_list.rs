@@ -0,0 +1,343 @@
+// A stack-like list that keeps up to N elements inline, without touching the heap.
+//
+// Every push onto a List allocates a node, which adds up when most lists only ever hold a
+// handful of elements. SmallList<T, N> stores its first N elements in an array inside the struct
+// itself, and only when an (N+1)th element arrives does it "spill": the elements move into a
+// regular List<T> and from then on it behaves exactly like one. It has the same push / pop / peek
+// / iter methods as List, with the most recently pushed element at the front.
+//
+// The array can't be a plain [T; N], because the slots past `len` hold no element yet. So it is
+// an array of MaybeUninit<T>: memory that may or may not contain a T, and it's up to us to only
+// read the slots we wrote, and to drop exactly those (see Inline's Drop).
+//
+// Once spilled, shrinking back to N elements doesn't move them inline right away, or a list going
+// back and forth across the boundary would copy everything on every push and pop. It waits until
+// the list is down to half the inline capacity instead.
+
+use crate::generic_lists::{self, List};
+use std::fmt;
+use std::iter::Rev;
+use std::mem::{self, MaybeUninit};
+use std::slice;
+
+pub struct SmallList<T, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+enum Storage<T, const N: usize> {
+    Inline(Inline<T, N>),
+    Spilled(List<T>),
+}
+
+// The inline storage. Slots [0, len) hold elements, the last of them is the front of the list,
+// so pushing and popping happen at the end of the array like on a Vec.
+struct Inline<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> Inline<T, N> {
+    fn new() -> Self {
+        Inline { buf: [(); N].map(|_| MaybeUninit::uninit()), len: 0 }
+    }
+
+    // Hands the element back if there is no room for it.
+    fn push(&mut self, elem: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(elem);
+        }
+        self.buf[self.len].write(elem);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        // SAFETY: the slot was below len, so it holds an element, and lowering len first means
+        // it is never read (or dropped) again.
+        Some(unsafe { self.buf[self.len].assume_init_read() })
+    }
+
+    fn peek(&self) -> Option<&T> {
+        // SAFETY: slots below len hold elements.
+        self.len.checked_sub(1).map(|top| unsafe { self.buf[top].assume_init_ref() })
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in peek.
+        self.len.checked_sub(1).map(move |top| unsafe { self.buf[top].assume_init_mut() })
+    }
+
+    // Moves the elements into a List, keeping the front at the front.
+    fn into_list(mut self) -> List<T> {
+        // len goes to 0 first, so our Drop won't drop the elements we move out.
+        let len = mem::replace(&mut self.len, 0);
+        let mut list = List::new();
+        for slot in &self.buf[..len] {
+            // SAFETY: slots below the old len hold elements, and each one is read once.
+            list.push(unsafe { slot.assume_init_read() });
+        }
+        list
+    }
+
+    // The other way round. The caller makes sure the list fits (list.len() <= N).
+    fn from_list(list: List<T>) -> Self {
+        let mut inline = Inline::new();
+        let len = list.len();
+        // The list's front goes into the last slot, the one after it into the slot before, ...
+        for (slot, elem) in inline.buf[..len].iter_mut().rev().zip(list) {
+            slot.write(elem);
+        }
+        inline.len = len;
+        inline
+    }
+}
+
+// Drops the elements that are there and leaves the uninitialized slots alone.
+impl<T, const N: usize> Drop for Inline<T, N> {
+    fn drop(&mut self) {
+        let len = mem::replace(&mut self.len, 0);
+        for slot in &mut self.buf[..len] {
+            // SAFETY: slots below len hold elements, and each one is dropped once.
+            unsafe { slot.assume_init_drop() };
+        }
+    }
+}
+
+impl<T, const N: usize> SmallList<T, N> {
+    pub fn new() -> Self {
+        SmallList { storage: Storage::Inline(Inline::new()) }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        match &mut self.storage {
+            Storage::Spilled(list) => list.push(elem),
+            Storage::Inline(inline) => {
+                if let Err(elem) = inline.push(elem) {
+                    // Full: move everything into a List and continue there.
+                    let mut list = self.take_inline().into_list();
+                    list.push(elem);
+                    self.storage = Storage::Spilled(list);
+                }
+            }
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.pop(),
+            Storage::Spilled(list) => {
+                let elem = list.pop();
+                if list.len() <= N / 2 {
+                    let list = mem::take(list);
+                    self.storage = Storage::Inline(Inline::from_list(list));
+                }
+                elem
+            }
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        match &self.storage {
+            Storage::Inline(inline) => inline.peek(),
+            Storage::Spilled(list) => list.peek(),
+        }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        match &mut self.storage {
+            Storage::Inline(inline) => inline.peek_mut(),
+            Storage::Spilled(list) => list.peek_mut(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(inline) => inline.len,
+            Storage::Spilled(list) => list.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Whether the elements currently live in a heap allocated List.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.storage, Storage::Spilled(_))
+    }
+
+    // The elements front first, like List::iter.
+    pub fn iter(&self) -> Iter<'_, T> {
+        match &self.storage {
+            Storage::Inline(inline) => Iter(IterInner::Inline(inline.buf[..inline.len].iter().rev())),
+            Storage::Spilled(list) => Iter(IterInner::Spilled(list.iter())),
+        }
+    }
+
+    // Swaps in an empty inline storage and hands back the old one.
+    fn take_inline(&mut self) -> Inline<T, N> {
+        match mem::replace(&mut self.storage, Storage::Inline(Inline::new())) {
+            Storage::Inline(inline) => inline,
+            Storage::Spilled(_) => unreachable!("take_inline on a spilled SmallList"),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallList<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, T>(IterInner<'a, T>);
+
+enum IterInner<'a, T> {
+    Inline(Rev<slice::Iter<'a, MaybeUninit<T>>>),
+    Spilled(generic_lists::Iter<'a, T>),
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match &mut self.0 {
+            // SAFETY: iter() only walks the slots below len.
+            IterInner::Inline(slots) => slots.next().map(|slot| unsafe { slot.assume_init_ref() }),
+            IterInner::Spilled(iter) => iter.next(),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallList<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SmallList;
+    use crate::alloc_counter::measure;
+    use std::rc::Rc;
+
+    fn contents<const N: usize>(list: &SmallList<i32, N>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn stays_inline_up_to_n() {
+        let (list, allocated) = measure(|| {
+            let mut list: SmallList<i32, 4> = SmallList::new();
+            for i in 1..=4 {
+                list.push(i);
+            }
+            list
+        });
+        assert_eq!(allocated.allocations, 0);
+        assert!(!list.is_spilled());
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.peek(), Some(&4));
+        assert_eq!(contents(&list), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn spills_and_comes_back() {
+        let mut list: SmallList<i32, 4> = SmallList::new();
+        for i in 1..=4 {
+            list.push(i);
+        }
+
+        // The fifth element doesn't fit, everything moves to the heap in the same order:
+        list.push(5);
+        assert!(list.is_spilled());
+        assert_eq!(contents(&list), [5, 4, 3, 2, 1]);
+        *list.peek_mut().unwrap() *= 10;
+
+        // Back at 4 it stays spilled, so pushing and popping at the boundary doesn't copy:
+        assert_eq!(list.pop(), Some(50));
+        assert!(list.is_spilled());
+        list.push(5);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+        assert!(list.is_spilled());
+
+        // At half the inline capacity it moves back:
+        assert_eq!(list.pop(), Some(3));
+        assert!(!list.is_spilled());
+        assert_eq!(contents(&list), [2, 1]);
+        assert_eq!(list.peek(), Some(&2));
+
+        // And it fills up inline again before spilling a second time:
+        for i in 3..=4 {
+            list.push(i);
+        }
+        assert!(!list.is_spilled());
+        list.push(5);
+        assert!(list.is_spilled());
+        assert_eq!(contents(&list), [5, 4, 3, 2, 1]);
+
+        while list.pop().is_some() {}
+        assert!(list.is_empty() && !list.is_spilled());
+        assert_eq!(list.peek(), None);
+    }
+
+    #[test]
+    fn drops_exactly_the_elements_it_holds() {
+        let rc = Rc::new(());
+
+        // Partially filled inline storage: only the 3 written slots are dropped.
+        let mut list: SmallList<Rc<()>, 4> = SmallList::new();
+        for _ in 0..3 {
+            list.push(rc.clone());
+        }
+        assert_eq!(Rc::strong_count(&rc), 4);
+        drop(list);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        // Spilled, and moved back inline: nothing is dropped twice or left behind.
+        let mut list: SmallList<Rc<()>, 4> = SmallList::new();
+        for _ in 0..6 {
+            list.push(rc.clone());
+        }
+        assert!(list.is_spilled());
+        drop(list);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let mut list: SmallList<Rc<()>, 4> = SmallList::new();
+        for _ in 0..6 {
+            list.push(rc.clone());
+        }
+        for _ in 0..5 {
+            list.pop();
+        }
+        assert!(!list.is_spilled());
+        assert_eq!(Rc::strong_count(&rc), 2);
+        drop(list);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn zero_inline_capacity() {
+        // With N = 0 every list with elements is spilled:
+        let mut list: SmallList<i32, 0> = SmallList::default();
+        assert!(!list.is_spilled());
+        list.push(1);
+        list.push(2);
+        assert!(list.is_spilled());
+        assert_eq!(format!("{:?}", list), "[2, 1]");
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(1));
+        assert!(!list.is_spilled());
+        assert_eq!(list.pop(), None);
+    }
+}
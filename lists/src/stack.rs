@@ -0,0 +1,64 @@
+// A stack: last in, first out. A singly linked list already is one (push and pop at the head are
+// O(1)), so this is a thin wrapper around List<T> that only exposes the stack operations. Code
+// using a Stack says what it does with it, and can't accidentally index into the middle.
+
+use crate::generic_lists::List;
+
+pub struct Stack<T> {
+    list: List<T>,
+}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack { list: List::new() }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.list.push(elem);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.list.pop()
+    }
+
+    // The element pop would return next.
+    pub fn peek(&self) -> Option<&T> {
+        self.list.peek()
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stack;
+
+    #[test]
+    fn last_in_first_out() {
+        let mut stack = Stack::default();
+        assert!(stack.is_empty());
+        stack.push('a');
+        stack.push('b');
+        stack.push('c');
+        assert_eq!((stack.len(), stack.peek()), (3, Some(&'c')));
+        assert_eq!(stack.pop(), Some('c'));
+        stack.push('d');
+        assert_eq!(stack.pop(), Some('d'));
+        assert_eq!(stack.pop(), Some('b'));
+        assert_eq!(stack.pop(), Some('a'));
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.peek(), None);
+    }
+}
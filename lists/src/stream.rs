@@ -0,0 +1,266 @@
+// A lazy list ("stream"): a persistent list whose tail is only built when someone asks for it.
+//
+// A node of the persistent list in third.rs holds an element and an Rc to the next node. Here the
+// "next" is a thunk instead: a closure that produces the rest of the stream when called. That
+// makes infinite lists possible, like all the natural numbers, since only the part somebody
+// actually looks at ever gets built:
+//
+//   Stream::iterate(0, |x| x + 1)  =  0 -> <thunk>
+//   after .tail()                  =  0 -> 1 -> <thunk>
+//
+// The thunk is memoized: the first `tail()` runs the closure and stores the result in place of
+// it, so every later `tail()` (on this stream or on any clone sharing the node) gets the very same
+// nodes back without running anything again. The state lives in a RefCell, because forcing
+// happens through a shared &Stream but still changes the node.
+//
+// Like the persistent list, streams share their nodes through Rc, and cloning one is cheap.
+
+use crate::third;
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+pub struct Stream<T> {
+    // None is the empty stream.
+    node: Option<Rc<StreamNode<T>>>,
+}
+
+struct StreamNode<T> {
+    head: T,
+    tail: RefCell<ThunkState<T>>,
+}
+
+enum ThunkState<T> {
+    // Not computed yet.
+    Pending(Box<dyn FnOnce() -> Stream<T>>),
+    // The closure is running right now. Seeing this again means the tail depends on itself.
+    Forcing,
+    // Computed: the rest of the stream.
+    Forced(Stream<T>),
+}
+
+impl<T> StreamNode<T> {
+    fn force(&self) -> Stream<T> {
+        // Take the closure out before calling it: it may force other nodes, which is fine, but the
+        // RefCell must not be borrowed while it runs.
+        let state = mem::replace(&mut *self.tail.borrow_mut(), ThunkState::Forcing);
+        let tail = match state {
+            ThunkState::Forced(tail) => tail,
+            ThunkState::Pending(thunk) => thunk(),
+            ThunkState::Forcing => panic!("the tail of a stream depends on itself"),
+        };
+        *self.tail.borrow_mut() = ThunkState::Forced(tail.clone());
+        tail
+    }
+}
+
+impl<T> Stream<T> {
+    pub fn empty() -> Self {
+        Stream { node: None }
+    }
+
+    // A stream starting with `head`, whose rest is whatever `tail` returns, once somebody asks.
+    pub fn cons<F: FnOnce() -> Stream<T> + 'static>(head: T, tail: F) -> Self {
+        let tail = RefCell::new(ThunkState::Pending(Box::new(tail)));
+        Stream { node: Some(Rc::new(StreamNode { head, tail })) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node.is_none()
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.node.as_ref().map(|node| &node.head)
+    }
+
+    // The stream without its first element. The first call runs the thunk, later ones return the
+    // stored result. The tail of an empty stream is empty.
+    pub fn tail(&self) -> Stream<T> {
+        match &self.node {
+            Some(node) => node.force(),
+            None => Stream::empty(),
+        }
+    }
+
+    // The first n elements (fewer if the stream ends before that) as an eager persistent list.
+    // Only the tails needed for those n elements are forced.
+    pub fn take(&self, n: usize) -> third::List<T>
+    where
+        T: Clone,
+    {
+        let mut elems = Vec::new();
+        let mut cur = self.clone();
+        while elems.len() < n {
+            match cur.head() {
+                Some(head) => elems.push(head.clone()),
+                None => break,
+            }
+            if elems.len() < n {
+                cur = cur.tail();
+            }
+        }
+        elems.into_iter().collect()
+    }
+}
+
+impl<T: Clone + 'static> Stream<T> {
+    // The infinite stream seed, f(seed), f(f(seed)), ...
+    pub fn iterate<F: Fn(&T) -> T + 'static>(seed: T, f: F) -> Self {
+        iterate_rc(seed, Rc::new(f))
+    }
+}
+
+// Every thunk needs its own handle on `f`, hence the Rc.
+fn iterate_rc<T: Clone + 'static, F: Fn(&T) -> T + 'static>(seed: T, f: Rc<F>) -> Stream<T> {
+    let next = seed.clone();
+    Stream::cons(seed, move || iterate_rc(f(&next), f))
+}
+
+impl<T: 'static> Stream<T> {
+    // The stream of values f produces from a state: f(state) gives the first element and the
+    // state to continue from, or None to end the stream there. The first element is computed
+    // right away, the rest as they are forced.
+    pub fn unfold<S: 'static, F: Fn(S) -> Option<(T, S)> + 'static>(state: S, f: F) -> Self {
+        unfold_rc(state, Rc::new(f))
+    }
+}
+
+fn unfold_rc<T: 'static, S: 'static, F: Fn(S) -> Option<(T, S)> + 'static>(state: S, f: Rc<F>) -> Stream<T> {
+    match f(state) {
+        Some((elem, next)) => Stream::cons(elem, move || unfold_rc(next, f)),
+        None => Stream::empty(),
+    }
+}
+
+impl<T> Default for Stream<T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+// Shares every node, forced or not. Forcing through one clone forces it for all of them.
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Stream { node: self.node.clone() }
+    }
+}
+
+// The same problem and the same fix as the persistent list's Drop: a forced chain would
+// otherwise be dropped with one nested call per node. We unwrap nodes we own alone and move on
+// to their forced tail; an unforced tail just drops its closure.
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        let mut node = self.node.take();
+        while let Some(rc) = node {
+            node = match Rc::try_unwrap(rc) {
+                Ok(owned) => match owned.tail.into_inner() {
+                    ThunkState::Forced(mut tail) => tail.node.take(),
+                    _ => None,
+                },
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stream;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn contents<T: Clone>(stream: &Stream<T>, n: usize) -> Vec<T> {
+        stream.take(n).iter().cloned().collect()
+    }
+
+    #[test]
+    fn cons_head_tail() {
+        let stream = Stream::cons(1, || Stream::cons(2, Stream::empty));
+        assert_eq!(stream.head(), Some(&1));
+        assert_eq!(stream.tail().head(), Some(&2));
+        assert!(stream.tail().tail().is_empty());
+        assert!(stream.tail().tail().tail().is_empty());
+        assert_eq!(contents(&stream, 10), [1, 2]);
+        assert_eq!(Stream::<i32>::default().head(), None);
+    }
+
+    #[test]
+    fn thunks_run_at_most_once() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let stream = Stream::cons('a', move || {
+            counter.set(counter.get() + 1);
+            Stream::cons('b', Stream::empty)
+        });
+        assert_eq!(calls.get(), 0);
+
+        let first = stream.tail();
+        let second = stream.tail();
+        let from_clone = stream.clone().tail();
+        assert_eq!(calls.get(), 1);
+        assert_eq!(first.head(), Some(&'b'));
+        // All three are the same node:
+        assert!(Rc::ptr_eq(first.node.as_ref().unwrap(), second.node.as_ref().unwrap()));
+        assert!(Rc::ptr_eq(first.node.as_ref().unwrap(), from_clone.node.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn iterate() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let naturals = Stream::iterate(0, move |x| {
+            counter.set(counter.get() + 1);
+            x + 1
+        });
+        assert_eq!(contents(&naturals, 5), [0, 1, 2, 3, 4]);
+        // Five elements needed four steps, and taking them again needs none:
+        assert_eq!(calls.get(), 4);
+        assert_eq!(contents(&naturals, 5), [0, 1, 2, 3, 4]);
+        assert_eq!(calls.get(), 4);
+        assert_eq!(contents(&naturals, 7), [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(calls.get(), 6);
+        assert!(naturals.take(0).head().is_none());
+
+        let powers = Stream::iterate(1u64, |x| x * 2);
+        assert_eq!(powers.tail().tail().tail().head(), Some(&8));
+    }
+
+    #[test]
+    fn unfold() {
+        // Fibonacci numbers, infinite:
+        let fib = Stream::unfold((0u64, 1u64), |(a, b)| Some((a, (b, a + b))));
+        assert_eq!(contents(&fib, 10), [0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+
+        // A countdown that ends:
+        let countdown = Stream::unfold(3, |n| if n > 0 { Some((n, n - 1)) } else { None });
+        assert_eq!(contents(&countdown, 10), [3, 2, 1]);
+        assert!(Stream::unfold(0, |_: i32| None::<(i32, i32)>).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "the tail of a stream depends on itself")]
+    fn self_dependent_tail_panics() {
+        let cell: Rc<Cell<Option<Stream<i32>>>> = Rc::new(Cell::new(None));
+        let inside = cell.clone();
+        let stream = Stream::cons(1, move || {
+            let me = inside.take().unwrap();
+            me.tail()
+        });
+        cell.set(Some(stream.clone()));
+        stream.tail();
+    }
+
+    #[test]
+    fn long_forced_stream_drop() {
+        let n = if cfg!(miri) { 1_000 } else { 200_000 };
+        let naturals = Stream::iterate(0u32, |x| x + 1);
+        let mut cur = naturals.clone();
+        for _ in 0..n {
+            cur = cur.tail();
+        }
+        assert_eq!(cur.head(), Some(&n));
+        drop(cur);
+        // Dropping the head of a long forced chain must not overflow the stack.
+        drop(naturals);
+    }
+}
@@ -0,0 +1,1040 @@
+// Learning linked lists, part three: a persistent immutable singly-linked stack.
+//
+// The lists so far owned their nodes (Box), so every node belonged to exactly one list. A
+// persistent list never changes once it is built. Instead of pushing and popping, `prepend`
+// and `tail` give you a *new* list and leave the old one alone:
+//
+//   list1 = A -> B -> C -> D
+//   list2 = tail(list1) = B -> C -> D
+//   list3 = prepend(list2, X) = X -> B -> C -> D
+//
+// All three share the nodes B, C and D in memory:
+//
+//   list1 -> A ---+
+//                 |
+//                 v
+//   list2 ------> B -> C -> D
+//                 ^
+//                 |
+//   list3 -> X ---+
+//
+// A node with several owners can't be in a Box, so the nodes are reference counted (Rc): cloning
+// an Rc only bumps a counter, and the node is freed when the last Rc pointing at it goes away.
+// The price is that we can never get a &mut to an element, because someone else may be looking
+// at it.
+//
+// This exercise comes from https://rust-unofficial.github.io/too-many-lists/third.html
+//
+// Rc's counter is a plain integer, so an Rc can't go to another thread (two threads bumping it at
+// once would corrupt it). Arc is the same thing with an atomic counter, which makes it slower
+// but thread-safe. Rather than writing the list twice, the pointer type is a parameter: the
+// second parameter of List picks the family of pointers the nodes are linked with, RcKind (the
+// default) or ArcKind. `Rc` or `Arc` themselves can't be the parameter, because they aren't
+// types until they get their own parameter (Rc<Node<T>>), hence the two marker types.
+//
+//   RcList<T>  = List<T, RcKind>   single-threaded, the cheaper one
+//   ArcList<T> = List<T, ArcKind>  Send + Sync when T is
+
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+// The reference counted pointers a List can be built from. The trait is "sealed": it requires
+// a trait from a private module, so code outside this file can use it but can't implement it
+// for its own pointers.
+pub trait RcLike: sealed::Sealed {
+    // The pointer to a U in this family, e.g. Rc<U>.
+    type Pointer<U>: Deref<Target = U> + Clone;
+
+    fn new<U>(value: U) -> Self::Pointer<U>;
+
+    // The value if this was the last pointer to it, None otherwise (the pointer is dropped then).
+    // Unlike try_unwrap, when several threads drop their pointers at the same time, exactly one
+    // of them gets the value.
+    fn into_inner<U>(this: Self::Pointer<U>) -> Option<U>;
+
+    // Whether both point at the very same allocation (not just equal values).
+    fn ptr_eq<U>(a: &Self::Pointer<U>, b: &Self::Pointer<U>) -> bool;
+
+    // A &mut to the value, cloning it into a new allocation first if it is shared.
+    fn make_mut<U: Clone>(this: &mut Self::Pointer<U>) -> &mut U;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+// Marker types, never instantiated.
+pub enum RcKind {}
+pub enum ArcKind {}
+
+impl sealed::Sealed for RcKind {}
+impl sealed::Sealed for ArcKind {}
+
+impl RcLike for RcKind {
+    type Pointer<U> = Rc<U>;
+
+    fn new<U>(value: U) -> Rc<U> {
+        Rc::new(value)
+    }
+
+    fn into_inner<U>(this: Rc<U>) -> Option<U> {
+        Rc::into_inner(this)
+    }
+
+    fn ptr_eq<U>(a: &Rc<U>, b: &Rc<U>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+
+    fn make_mut<U: Clone>(this: &mut Rc<U>) -> &mut U {
+        Rc::make_mut(this)
+    }
+}
+
+impl RcLike for ArcKind {
+    type Pointer<U> = Arc<U>;
+
+    fn new<U>(value: U) -> Arc<U> {
+        Arc::new(value)
+    }
+
+    fn into_inner<U>(this: Arc<U>) -> Option<U> {
+        Arc::into_inner(this)
+    }
+
+    fn ptr_eq<U>(a: &Arc<U>, b: &Arc<U>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+
+    fn make_mut<U: Clone>(this: &mut Arc<U>) -> &mut U {
+        Arc::make_mut(this)
+    }
+}
+
+pub struct List<T, P: RcLike = RcKind> {
+    head: Link<T, P>,
+}
+
+pub type RcList<T> = List<T, RcKind>;
+pub type ArcList<T> = List<T, ArcKind>;
+
+type Link<T, P> = Option<<P as RcLike>::Pointer<Node<T, P>>>;
+
+// Every node knows the length of the list that starts at it. A node never changes once it is
+// built, and neither does everything after it, so the number stays right however many lists end
+// up sharing the node, and len() is just a look at the head.
+struct Node<T, P: RcLike> {
+    elem: T,
+    len: usize,
+    next: Link<T, P>,
+}
+
+// Copying a node (for make_mut, see head_mut) copies the element but shares the rest of the list.
+// Written by hand because derive(Clone) would want P: Clone too.
+impl<T: Clone, P: RcLike> Clone for Node<T, P> {
+    fn clone(&self) -> Self {
+        Node { elem: self.elem.clone(), len: self.len, next: self.next.clone() }
+    }
+}
+
+// `new` is only defined for the Rc version, the way HashMap::new only exists for the default
+// hasher: a default type parameter doesn't help type inference, so a generic `new` would make
+// every `List::new()` need a type annotation. Use ArcList::default() for an empty ArcList.
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None }
+    }
+}
+
+impl<T, P: RcLike> List<T, P> {
+    // A new list with `elem` in front of this one. The old list is still there, and shares all
+    // of its nodes with the new one (the clone only bumps the reference count of our head).
+    pub fn prepend(&self, elem: T) -> List<T, P> {
+        List { head: Some(P::new(Node { elem, len: self.len() + 1, next: self.head.clone() })) }
+    }
+
+    // The list without its first element, sharing everything after it. The tail of an empty
+    // list is empty.
+    pub fn tail(&self) -> List<T, P> {
+        List { head: self.head.as_ref().and_then(|node| node.next.clone()) }
+    }
+
+    // O(1), see Node.
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // The element at `index`, or None if the list is shorter than that. Walking to it is
+    // O(index), but an index past the end is turned down without walking at all.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        self.iter().nth(index)
+    }
+
+    // The first element.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    // The same three operations under their Lisp names, where this kind of list comes from:
+    // (cons 1 (cons 2 nil)) is List::cons(1, List::cons(2, List::new())), car is the head and
+    // cdr the tail. Only cons is different from prepend: it takes the list by value, which
+    // saves the reference count bump when the old list isn't needed any more.
+    pub fn cons(elem: T, mut list: List<T, P>) -> List<T, P> {
+        list.push_mut(elem);
+        list
+    }
+
+    pub fn car(&self) -> Option<&T> {
+        self.head()
+    }
+
+    pub fn cdr(&self) -> List<T, P> {
+        self.tail()
+    }
+
+    // prepend, but changing this list instead of making a new one. Nothing is copied: the old
+    // head just becomes the new node's next.
+    pub fn push_mut(&mut self, elem: T) {
+        let next = self.head.take();
+        let len = next.as_ref().map_or(0, |node| node.len) + 1;
+        self.head = Some(P::new(Node { elem, len, next }));
+    }
+
+    // The first element, mutably. The head node may be shared with other lists, and they must
+    // not see the change, so this is clone-on-write (Rc::make_mut / Arc::make_mut): if this list
+    // is the only owner of the node it is changed in place, otherwise the node is first copied
+    // (with a clone of its element) and this list switches to the copy. Only the one node is
+    // copied, the rest of the list stays shared either way.
+    pub fn head_mut(&mut self) -> Option<&mut T>
+    where
+        T: Clone,
+    {
+        self.head.as_mut().map(|node| &mut P::make_mut(node).elem)
+    }
+
+    // self followed by other. The nodes of `other` are shared as they are (the result just points
+    // at other's head), but self's nodes can't be: the last of them would have to point at
+    // other's head instead of wherever it points now, and we can't change a shared node. So
+    // self's elements are cloned into new nodes, prepended back to front onto other.
+    pub fn append(&self, other: &List<T, P>) -> List<T, P>
+    where
+        T: Clone,
+    {
+        let elems: Vec<&T> = self.iter().collect();
+        elems.into_iter().rev().fold(other.clone(), |list, elem| list.prepend(elem.clone()))
+    }
+
+    // A list that is the same as this one except that the element at `index` is `value`, or None
+    // if there is no such element. Nodes can't be changed in place, so this "path copies": the
+    // nodes from the head down to `index` are recreated (the ones in front of it with clones of
+    // their elements), and everything after `index` is shared with the old list. O(index).
+    pub fn update(&self, index: usize, value: T) -> Option<List<T, P>>
+    where
+        T: Clone,
+    {
+        if index >= self.len() {
+            return None;
+        }
+        let mut before = Vec::with_capacity(index);
+        let mut cur = self.head.as_deref();
+        for _ in 0..index {
+            let node = cur?;
+            before.push(&node.elem);
+            cur = node.next.as_deref();
+        }
+        let target = cur?;
+
+        let rest = List { head: target.next.clone() };
+        Some(before.into_iter().rev().fold(rest.prepend(value), |list, elem| list.prepend(elem.clone())))
+    }
+
+    // A reversed copy. Walking the list front to back and prepending every element onto a new
+    // list reverses it by itself. Every element is cloned: no node can be shared, since each one
+    // needs a different `next` than it has in this list.
+    pub fn rev(&self) -> List<T, P>
+    where
+        T: Clone,
+    {
+        self.iter().fold(List::default(), |list, elem| list.prepend(elem.clone()))
+    }
+
+    // A new list with `f` applied to every element, in the same order. Nothing is shared with
+    // this list (the elements are new ones). Collecting builds the list in a loop (see
+    // FromIterator below), so long lists are no problem for the stack.
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> List<U, P> {
+        self.iter().map(f).collect()
+    }
+
+    // A new list with clones of the elements `f` keeps, in the same order. A kept suffix could
+    // in principle be shared, but that would need a second pass to find where it starts; this
+    // keeps it simple and copies.
+    pub fn filter<F: Fn(&T) -> bool>(&self, f: F) -> List<T, P>
+    where
+        T: Clone,
+    {
+        self.iter().filter(|elem| f(elem)).cloned().collect()
+    }
+
+    // How many nodes at the end this list shares with `other`: the same nodes in memory, not just
+    // equal elements. Once two lists reach a shared node, everything after it is shared too, and
+    // a shared node is the same distance from the end in both. So we skip ahead in the longer
+    // list until both have the same length left, then walk them side by side until they meet.
+    pub fn shared_suffix_len(&self, other: &List<T, P>) -> usize {
+        let (mut a, mut b) = (&self.head, &other.head);
+        for _ in other.len()..self.len() {
+            a = &a.as_ref().unwrap().next;
+        }
+        for _ in self.len()..other.len() {
+            b = &b.as_ref().unwrap().next;
+        }
+        while let (Some(x), Some(y)) = (a, b) {
+            if P::ptr_eq(x, y) {
+                return x.len;
+            }
+            a = &x.next;
+            b = &y.next;
+        }
+        0
+    }
+
+    // The first index where the two lists have different elements, or where one of them ends.
+    // For equal lists that is their length. Reaching a shared node ends the walk early, like in
+    // eq: the rest is the same nodes.
+    pub fn diverges_at(&self, other: &List<T, P>) -> usize
+    where
+        T: PartialEq,
+    {
+        let (mut a, mut b) = (&self.head, &other.head);
+        let mut index = 0;
+        while let (Some(x), Some(y)) = (a, b) {
+            if P::ptr_eq(x, y) {
+                return index + x.len;
+            }
+            if x.elem != y.elem {
+                break;
+            }
+            a = &x.next;
+            b = &y.next;
+            index += 1;
+        }
+        index
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter { next: self.head.as_deref() }
+    }
+}
+
+impl<T, P: RcLike> Default for List<T, P> {
+    fn default() -> Self {
+        List { head: None }
+    }
+}
+
+// Collecting keeps the iterator's order, the first item ends up at the head. We can only add at
+// the front, so the items are buffered first and prepended back to front.
+impl<T, P: RcLike> FromIterator<T> for List<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items.into_iter().rev().fold(List::default(), |list, elem| list.prepend(elem))
+    }
+}
+
+// Two lists are equal if they have the same elements in the same order. Lists built from each
+// other often share a long suffix, and once both walks reach the same node, everything after it
+// is the same nodes too, so we can stop right there: comparing two lists that only differ in
+// their first few elements costs those few comparisons, however long the shared rest is.
+//
+// That shortcut assumes every element is equal to itself, which PartialEq alone doesn't promise
+// (f64::NAN != f64::NAN), so like Rc's own shortcut this needs T: Eq.
+//
+// Lists of different lengths can't be equal, and with the length cached that takes no
+// comparisons at all to find out.
+impl<T: Eq, P: RcLike> PartialEq for List<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let (mut a, mut b) = (&self.head, &other.head);
+        loop {
+            match (a, b) {
+                (None, None) => return true,
+                (Some(x), Some(y)) => {
+                    if P::ptr_eq(x, y) {
+                        return true;
+                    }
+                    if x.elem != y.elem {
+                        return false;
+                    }
+                    a = &x.next;
+                    b = &y.next;
+                }
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl<T: Eq, P: RcLike> Eq for List<T, P> {}
+
+// Equal lists have to hash the same, and equality looks at the elements only (sharing is just a
+// shortcut), so the hash is over every element plus the length, the way slices hash.
+impl<T: Hash, P: RcLike> Hash for List<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+        state.write_usize(self.len());
+    }
+}
+
+// A copy of the list that shares every node with it: only the head pointer is cloned.
+impl<T, P: RcLike> Clone for List<T, P> {
+    fn clone(&self) -> Self {
+        List { head: self.head.clone() }
+    }
+}
+
+// With the "serde" feature a list serializes as a plain sequence, head first, the same as a Vec
+// with those elements would. Sharing isn't visible in the output, so a list built on a shared
+// suffix looks exactly like one that was built from scratch, and deserializing always builds a
+// fresh chain that shares nothing.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, P: RcLike> serde::Serialize for List<T, P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, P: RcLike> serde::Deserialize<'de> for List<T, P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // The elements arrive head first, and FromIterator buffers them anyway, so going through
+        // a Vec costs nothing extra.
+        Vec::<T>::deserialize(deserializer).map(|elems| elems.into_iter().collect())
+    }
+}
+
+// The Drop the compiler generates would drop the head Rc, which drops its node, which drops the
+// next Rc, and so on: one nested call per node, which overflows the stack on a long list. So we
+// walk the list in a loop instead, like the other lists do. The difference here is that a node
+// may be shared. P::into_inner (Rc::into_inner or Arc::into_inner) hands us the node only if we
+// hold the last reference to it; then we take its `next` and let the node go. As soon as a node
+// is shared, into_inner just drops our reference (one strong count less), and we stop: the rest
+// of the list belongs to someone else too.
+//
+// try_unwrap would not do for Arc: if two threads drop the last two references at the same time,
+// both try_unwraps can fail, and whichever Err(arc) is dropped last then frees the node the
+// recursive way after all. into_inner always gives the node to one of them.
+impl<T, P: RcLike> Drop for List<T, P> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            match P::into_inner(node) {
+                Some(mut node) => head = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+// Iter works just like the one for the second list: it only borrows the nodes.
+pub struct Iter<'a, T, P: RcLike = RcKind> {
+    next: Option<&'a Node<T, P>>,
+}
+
+impl<'a, T, P: RcLike> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T, P: RcLike> IntoIterator for &'a List<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+
+    fn into_iter(self) -> Iter<'a, T, P> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArcList, List, RcList};
+    use std::hash::{Hash, Hasher};
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.head(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.head(), Some(&3));
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&2));
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&1));
+
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+
+        // Make sure the tail of an empty list works:
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let list = List::new().prepend(1).prepend(2).prepend(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), None);
+
+        let doubled: Vec<i32> = (&list).into_iter().map(|x| x * 2).collect();
+        assert_eq!(doubled, [6, 4, 2]);
+    }
+
+    #[test]
+    fn shared_tails() {
+        // The picture from the top of the file:
+        let list1 = List::new().prepend('D').prepend('C').prepend('B').prepend('A');
+        let list2 = list1.tail();
+        let list3 = list2.prepend('X');
+
+        assert_eq!(list1.iter().collect::<String>(), "ABCD");
+        assert_eq!(list2.iter().collect::<String>(), "BCD");
+        assert_eq!(list3.iter().collect::<String>(), "XBCD");
+
+        // Node B is owned by A's next, by list2's head and by X's next:
+        let b = list2.head.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(b), 3);
+        // Both list1 and list3 point at the very same node, not at copies of it:
+        assert!(Rc::ptr_eq(list1.head.as_ref().unwrap().next.as_ref().unwrap(), b));
+        assert!(Rc::ptr_eq(list3.head.as_ref().unwrap().next.as_ref().unwrap(), b));
+    }
+
+    #[test]
+    fn dropping_one_list_keeps_the_shared_suffix() {
+        let list1 = List::new().prepend(3).prepend(2).prepend(1);
+        let list2 = list1.tail().prepend(10);
+        let suffix = list1.tail();
+        // Node 2 is owned by node 1's next, by node 10's next and by suffix's head:
+        assert_eq!(Rc::strong_count(suffix.head.as_ref().unwrap()), 3);
+
+        // Dropping list1 only frees its own first node:
+        drop(list1);
+        assert_eq!(Rc::strong_count(suffix.head.as_ref().unwrap()), 2);
+        assert_eq!(list2.iter().copied().collect::<Vec<_>>(), [10, 2, 3]);
+
+        drop(list2);
+        assert_eq!(Rc::strong_count(suffix.head.as_ref().unwrap()), 1);
+        assert_eq!(suffix.iter().copied().collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn long_list_drop() {
+        // A million uniquely owned nodes; the generated recursive Drop overflows the stack here.
+        let n = if cfg!(miri) { 1_000 } else { 1_000_000 };
+        let mut list = List::new();
+        for i in 0..n {
+            list = list.prepend(i);
+        }
+        drop(list);
+    }
+
+    #[test]
+    fn drop_stops_at_shared_nodes() {
+        let shared = List::new().prepend(3).prepend(2);
+        let list = shared.prepend(1).prepend(0);
+
+        // Node 2 is owned by shared's head and by node 1's next:
+        let node_2 = shared.head.clone().unwrap();
+        assert_eq!(Rc::strong_count(&node_2), 3);
+        let node_3 = node_2.next.clone().unwrap();
+        assert_eq!(Rc::strong_count(&node_3), 2);
+
+        // Dropping `list` frees nodes 0 and 1, and gives up exactly one reference to node 2.
+        // Node 3 isn't touched at all:
+        drop(list);
+        assert_eq!(Rc::strong_count(&node_2), 2);
+        assert_eq!(Rc::strong_count(&node_3), 2);
+        assert_eq!(shared.iter().copied().collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn append_shares_the_second_list() {
+        let front = List::new().prepend(2).prepend(1);
+        let back = List::new().prepend(4).prepend(3);
+        let back_head = || back.head.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(back_head()), 1);
+
+        let both = front.append(&back);
+        assert_eq!(both.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+        // back's nodes are now also pointed at by node 2 of `both`, and weren't copied:
+        assert_eq!(Rc::strong_count(back_head()), 2);
+        assert!(Rc::ptr_eq(both.tail().tail().head.as_ref().unwrap(), back_head()));
+        // front's nodes were copied, nothing new points at them:
+        assert_eq!(Rc::strong_count(front.head.as_ref().unwrap()), 1);
+        assert_eq!(front.iter().copied().collect::<Vec<_>>(), [1, 2]);
+
+        // Appending to or from an empty list:
+        let empty = List::new();
+        assert_eq!(empty.append(&back).iter().copied().collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(front.append(&empty).iter().copied().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn update_copies_only_the_path() {
+        let list = List::new().prepend(4).prepend(3).prepend(2).prepend(1);
+        let node = |list: &List<i32>, index: usize| {
+            let mut cur = list.head.clone().unwrap();
+            for _ in 0..index {
+                cur = cur.next.clone().unwrap();
+            }
+            cur
+        };
+
+        // Updating the head makes one new node, the rest is shared:
+        let updated = list.update(0, 10).unwrap();
+        assert_eq!(updated.iter().copied().collect::<Vec<_>>(), [10, 2, 3, 4]);
+        assert!(!Rc::ptr_eq(&node(&updated, 0), &node(&list, 0)));
+        assert!(Rc::ptr_eq(&node(&updated, 1), &node(&list, 1)));
+        // Node 2 is pointed at by both heads' next now (`node` holds one more while it's around):
+        assert_eq!(Rc::strong_count(&node(&list, 1)), 3);
+
+        // Updating index 2 recreates nodes 0 to 2 and shares node 3:
+        let updated = list.update(2, 30).unwrap();
+        assert_eq!(updated.iter().copied().collect::<Vec<_>>(), [1, 2, 30, 4]);
+        for index in 0..3 {
+            assert!(!Rc::ptr_eq(&node(&updated, index), &node(&list, index)));
+        }
+        assert!(Rc::ptr_eq(&node(&updated, 3), &node(&list, 3)));
+
+        // The last element, and past the end:
+        assert_eq!(list.update(3, 40).unwrap().iter().copied().collect::<Vec<_>>(), [1, 2, 3, 40]);
+        assert!(list.update(4, 50).is_none());
+        assert!(List::new().update(0, 1).is_none());
+
+        // The original never changed:
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn from_iter_keeps_the_order() {
+        let list: List<i32> = (0..5).collect();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        assert_eq!(list.head(), Some(&0));
+
+        let empty: List<i32> = std::iter::empty().collect();
+        assert_eq!(empty.head(), None);
+    }
+
+    #[test]
+    fn rev() {
+        let list: List<i32> = (0..5).collect();
+        let shared = list.tail();
+
+        let reversed = list.rev();
+        assert_eq!(reversed.iter().copied().collect::<Vec<_>>(), [4, 3, 2, 1, 0]);
+        // The original, and the list sharing its nodes, are untouched:
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        assert_eq!(shared.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(Rc::strong_count(shared.head.as_ref().unwrap()), 2);
+
+        // Reversing twice gives the same elements back (in new nodes):
+        assert!(reversed.rev().iter().eq(list.iter()));
+        assert_eq!(List::<i32>::new().rev().head(), None);
+    }
+
+    // Counts how often two of them are compared, to see the shared-suffix shortcut at work.
+    #[derive(Debug, Clone, Eq)]
+    struct Counted(u32);
+
+    thread_local! {
+        static COMPARISONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl PartialEq for Counted {
+        fn eq(&self, other: &Self) -> bool {
+            COMPARISONS.with(|count| count.set(count.get() + 1));
+            self.0 == other.0
+        }
+    }
+
+    fn comparisons<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        let before = COMPARISONS.with(|count| count.get());
+        let result = f();
+        (result, COMPARISONS.with(|count| count.get()) - before)
+    }
+
+    #[test]
+    fn eq_stops_at_a_shared_suffix() {
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let suffix: List<Counted> = (0..n).map(Counted).collect();
+        let a = suffix.prepend(Counted(1)).prepend(Counted(2));
+        let b = suffix.prepend(Counted(1)).prepend(Counted(2));
+        let c = suffix.prepend(Counted(9)).prepend(Counted(2));
+
+        // Only the two nodes in front of the shared suffix are compared:
+        assert_eq!(comparisons(|| a == b), (true, 2));
+        assert_eq!(comparisons(|| a != c), (true, 2));
+        // The same list shares everything, a difference up front ends it right away:
+        assert_eq!(comparisons(|| a == a.clone()), (true, 0));
+        // Different lengths don't need to look at any element:
+        assert_eq!(comparisons(|| a == suffix), (false, 0));
+        assert_eq!(comparisons(|| c == b), (false, 2));
+
+        // Without sharing every element has to be compared:
+        let copy: List<Counted> = a.iter().cloned().collect();
+        assert_eq!(comparisons(|| a == copy), (true, n as usize + 2));
+    }
+
+    #[test]
+    fn eq_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+
+        fn hash_of(list: &List<i32>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            list.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let list: List<i32> = (1..=3).collect();
+        let copy: List<i32> = (1..=3).collect();
+        let shared = list.tail().prepend(1);
+        assert!(list == copy && list == shared);
+        assert_eq!(hash_of(&list), hash_of(&copy));
+        assert_eq!(hash_of(&list), hash_of(&shared));
+
+        assert!(list != list.tail());
+        assert!(list != list.update(2, 4).unwrap());
+        assert!(List::<i32>::new() == List::new());
+
+        let set: HashSet<List<i32>> = vec![list, copy, shared, (1..=2).collect()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn repeated_tails_share_nodes() {
+        let list: List<i32> = [1, 2, 3, 4].iter().copied().collect();
+        let tails: Vec<List<i32>> = (0..3).map(|_| list.tail()).collect();
+        let second = list.head.as_ref().unwrap().next.as_ref().unwrap();
+        for tail in &tails {
+            assert_eq!(tail.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+            // The very same nodes as the original's, nothing was copied:
+            assert!(Rc::ptr_eq(tail.head.as_ref().unwrap(), second));
+        }
+        // The original's second node is held by the head plus the three tails:
+        assert_eq!(Rc::strong_count(tails[0].head.as_ref().unwrap()), 4);
+        drop(tails);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn cons_car_cdr() {
+        // (cons 1 (cons 2 (cons 3 nil)))
+        let list: List<i32> = List::cons(1, List::cons(2, List::cons(3, List::new())));
+        assert_eq!(list.car(), Some(&1));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.cdr().car(), Some(&2));
+        assert_eq!(list.cdr().cdr().car(), Some(&3));
+        assert_eq!(list.cdr().len(), 2);
+        assert!(list.cdr().cdr().cdr().is_empty());
+        assert_eq!(List::<i32>::new().car(), None);
+        assert!(List::<i32>::new().cdr().is_empty());
+
+        // Every cdr is the same second node, not a copy of it:
+        let (a, b) = (list.cdr(), list.cdr());
+        assert!(Rc::ptr_eq(a.head.as_ref().unwrap(), b.head.as_ref().unwrap()));
+        assert!(Rc::ptr_eq(a.head.as_ref().unwrap(), list.head.as_ref().unwrap().next.as_ref().unwrap()));
+
+        // cons onto a cdr shares it too:
+        let other = List::cons(10, a);
+        assert_eq!(other.iter().copied().collect::<Vec<_>>(), [10, 2, 3]);
+        assert!(Rc::ptr_eq(other.head.as_ref().unwrap().next.as_ref().unwrap(), b.head.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn cached_len() {
+        let empty: List<i32> = List::new();
+        assert_eq!((empty.len(), empty.is_empty()), (0, true));
+        assert_eq!(empty.tail().len(), 0);
+
+        let mut list = List::new();
+        for i in 1..=10 {
+            list = list.prepend(i);
+            assert_eq!(list.len(), i);
+            assert_eq!(list.iter().count(), list.len());
+        }
+        for i in (0..10).rev() {
+            list = list.tail();
+            assert_eq!(list.len(), i);
+            assert_eq!(list.iter().count(), list.len());
+        }
+        assert!(list.is_empty());
+
+        // Lists sharing a suffix each have their own length, and the suffix keeps its own:
+        let suffix: List<i32> = (1..=5).collect();
+        let a = suffix.prepend(0);
+        let b = suffix.prepend(0).prepend(-1).prepend(-2);
+        let c = b.tail().tail();
+        assert_eq!((suffix.len(), a.len(), b.len(), c.len()), (5, 6, 8, 6));
+        drop(a);
+        drop(b);
+        assert_eq!((suffix.len(), c.len()), (5, 6));
+
+        // And the other ways of building lists keep it right too:
+        assert_eq!(suffix.append(&c).len(), 11);
+        assert_eq!(suffix.update(4, 50).unwrap().len(), 5);
+        assert_eq!(suffix.rev().len(), 5);
+        assert_eq!(suffix.filter(|x| x % 2 == 0).len(), 2);
+        for list in [suffix.append(&c), suffix.rev(), suffix.filter(|x| x % 2 == 0)] {
+            assert_eq!(list.iter().count(), list.len());
+        }
+    }
+
+    #[test]
+    fn get() {
+        let list: List<i32> = (10..15).collect();
+        assert_eq!(list.get(0), Some(&10));
+        assert_eq!(list.get(4), Some(&14));
+        assert_eq!(list.get(5), None);
+        assert_eq!(list.get(usize::MAX), None);
+        assert_eq!(list.tail().get(3), Some(&14));
+        assert_eq!(List::<i32>::new().get(0), None);
+        assert_eq!(list.update(5, 0).map(|list| list.len()), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let list: List<i32> = (1..=4).collect();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3,4]");
+        let back: List<i32> = serde_json::from_str(&json).unwrap();
+        assert!(back == list);
+        assert_eq!(back.len(), 4);
+
+        let empty: List<String> = List::new();
+        assert_eq!(serde_json::to_string(&empty).unwrap(), "[]");
+        let back: List<String> = serde_json::from_str("[]").unwrap();
+        assert!(back.is_empty());
+
+        // Shared suffixes don't show up in the output, and the result shares nothing:
+        let suffix: List<i32> = (2..=4).collect();
+        let a = suffix.prepend(1);
+        let b = suffix.prepend(0);
+        assert_eq!(serde_json::to_string(&a).unwrap(), json);
+        assert_eq!(serde_json::to_string(&b).unwrap(), "[0,2,3,4]");
+        let back: List<i32> = serde_json::from_str(&serde_json::to_string(&a).unwrap()).unwrap();
+        assert_eq!(Rc::strong_count(suffix.head.as_ref().unwrap()), 3);
+        assert_eq!(Rc::strong_count(back.head.as_ref().unwrap().next.as_ref().unwrap()), 1);
+
+        let arc: ArcList<String> = serde_json::from_str(r#"["x","y"]"#).unwrap();
+        assert_eq!(arc.iter().map(String::as_str).collect::<Vec<_>>(), ["x", "y"]);
+        assert!(serde_json::from_str::<List<i32>>("[1,\"two\"]").is_err());
+    }
+
+    #[test]
+    fn push_mut_and_head_mut() {
+        // Counts its clones, so we can see when head_mut has to copy.
+        #[derive(Debug, PartialEq, Eq)]
+        struct Cloned(u32);
+
+        thread_local! {
+            static CLONES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+
+        impl Clone for Cloned {
+            fn clone(&self) -> Self {
+                CLONES.with(|count| count.set(count.get() + 1));
+                Cloned(self.0)
+            }
+        }
+
+        fn clones() -> usize {
+            CLONES.with(|count| count.get())
+        }
+
+        // Owned by this list alone: everything happens in place.
+        let mut list = List::new();
+        for i in 1..=3 {
+            list.push_mut(Cloned(i));
+        }
+        assert_eq!(list.len(), 3);
+        list.head_mut().unwrap().0 = 30;
+        list.head_mut().unwrap().0 += 1;
+        assert_eq!(clones(), 0);
+        assert_eq!(list.iter().map(|c| c.0).collect::<Vec<_>>(), [31, 2, 1]);
+
+        // Shared with another handle: the head node is copied once, the rest stays shared.
+        let other = list.clone();
+        list.head_mut().unwrap().0 = 100;
+        assert_eq!(clones(), 1);
+        assert_eq!(list.iter().map(|c| c.0).collect::<Vec<_>>(), [100, 2, 1]);
+        assert_eq!(other.iter().map(|c| c.0).collect::<Vec<_>>(), [31, 2, 1]);
+        let second = |list: &List<Cloned>| list.tail().head.clone().unwrap();
+        assert!(Rc::ptr_eq(&second(&list), &second(&other)));
+        // Now the copy is ours alone:
+        list.head_mut().unwrap().0 = 101;
+        assert_eq!(clones(), 1);
+
+        // push_mut on a shared list leaves the other handle alone too:
+        let mut pushed = other.clone();
+        pushed.push_mut(Cloned(0));
+        assert_eq!(pushed.len(), 4);
+        assert_eq!(other.len(), 3);
+        assert_eq!(other.head(), Some(&Cloned(31)));
+        assert_eq!(clones(), 1);
+
+        assert_eq!(List::<Cloned>::new().head_mut(), None);
+    }
+
+    #[test]
+    fn shared_suffix_and_divergence() {
+        let base: List<i32> = (1..=4).collect();
+        let a = base.prepend(20).prepend(10);
+        let b = base.prepend(30);
+        assert_eq!(a.shared_suffix_len(&b), 4);
+        assert_eq!(b.shared_suffix_len(&a), 4);
+        assert_eq!(a.shared_suffix_len(&base), 4);
+        assert_eq!(a.shared_suffix_len(&a.tail().tail().tail()), 3);
+        assert_eq!(a.diverges_at(&b), 0);
+        assert_eq!(a.diverges_at(&base.prepend(20).prepend(10)), 6);
+        assert_eq!(a.diverges_at(&base.prepend(21).prepend(10)), 1);
+        // One is a prefix of the other:
+        assert_eq!(a.diverges_at(&a.tail()), 0);
+        let short: List<i32> = vec![10, 20].into_iter().collect();
+        assert_eq!(a.diverges_at(&short), 2);
+        assert_eq!(short.diverges_at(&a), 2);
+
+        // Identical lists share everything:
+        let same = a.clone();
+        assert_eq!(a.shared_suffix_len(&same), 6);
+        assert_eq!(a.diverges_at(&same), 6);
+
+        // Disjoint lists share nothing, even with equal elements:
+        let copy: List<i32> = a.iter().copied().collect();
+        assert_eq!(a.shared_suffix_len(&copy), 0);
+        assert_eq!(a.diverges_at(&copy), 6);
+        let other: List<i32> = (5..8).collect();
+        assert_eq!(a.shared_suffix_len(&other), 0);
+        assert_eq!(a.diverges_at(&other), 0);
+        assert_eq!(a.shared_suffix_len(&List::new()), 0);
+        assert_eq!(List::<i32>::new().diverges_at(&List::new()), 0);
+    }
+
+    #[test]
+    fn map_and_filter() {
+        let list: List<i32> = (1..=6).collect();
+
+        let squares = list.map(|x| x * x);
+        assert_eq!(squares.iter().copied().collect::<Vec<_>>(), [1, 4, 9, 16, 25, 36]);
+        let strings: List<String> = list.map(|x| x.to_string());
+        assert_eq!(strings.head().map(String::as_str), Some("1"));
+
+        let evens = list.filter(|x| x % 2 == 0);
+        assert_eq!(evens.iter().copied().collect::<Vec<_>>(), [2, 4, 6]);
+        assert_eq!(list.filter(|_| false).head(), None);
+        assert_eq!(List::<i32>::new().map(|x| x + 1).head(), None);
+
+        // The original is untouched and shares nothing with the results:
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(Rc::strong_count(list.head.as_ref().unwrap()), 1);
+    }
+
+    #[test]
+    fn map_and_filter_long_lists() {
+        let n = if cfg!(miri) { 1_000 } else { 200_000 };
+        let list: List<u32> = (0..n).collect();
+        let doubled = list.map(|x| x * 2);
+        let odd = list.filter(|x| x % 2 == 1);
+        assert_eq!(doubled.iter().count(), n as usize);
+        assert_eq!(doubled.iter().nth(10), Some(&20));
+        assert_eq!(odd.iter().count(), n as usize / 2);
+        assert_eq!(odd.head(), Some(&1));
+    }
+
+    #[test]
+    fn arc_list_across_threads() {
+        use std::thread;
+
+        let shared = ArcList::default().prepend(2).prepend(1);
+
+        // Every thread gets its own handle on the shared nodes and builds on top of them:
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let base = shared.clone();
+                thread::spawn(move || {
+                    let own = base.prepend(t * 10).prepend(t * 10 + 1);
+                    assert_eq!(own.iter().copied().collect::<Vec<_>>(), [t * 10 + 1, t * 10, 1, 2]);
+                    own
+                })
+            })
+            .collect();
+        let lists: Vec<ArcList<u32>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // None of them changed the shared list or each other:
+        assert_eq!(shared.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        for (t, list) in (0..).zip(&lists) {
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), [t * 10 + 1, t * 10, 1, 2]);
+        }
+        // The shared head node is pointed at by `shared` and by the second node of each list:
+        assert_eq!(std::sync::Arc::strong_count(shared.head.as_ref().unwrap()), 5);
+    }
+
+    #[test]
+    fn long_arc_list_dropped_by_many_threads() {
+        use std::sync::{Arc, Barrier};
+        use std::thread;
+
+        // Every thread drops its clone at the same moment, so whichever drops the last reference
+        // to the first node has to walk the whole list, on a thread's smaller stack. (The race
+        // that used to free it recursively is narrow, so this won't catch it on every run.)
+        let n = if cfg!(miri) { 1_000 } else { 1_000_000 };
+        let threads = 8;
+        let list: ArcList<u32> = (0..n).collect();
+        let barrier = Arc::new(Barrier::new(threads));
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (list, barrier) = (list.clone(), barrier.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    drop(list);
+                })
+            })
+            .collect();
+        drop(list);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    // An ArcList can go to (and be shared between) threads if its elements can, an RcList never:
+    static_assertions::assert_impl_all!(ArcList<i32>: Send, Sync);
+    static_assertions::assert_not_impl_any!(ArcList<std::cell::Cell<i32>>: Sync);
+    static_assertions::assert_not_impl_any!(ArcList<Rc<i32>>: Send, Sync);
+    static_assertions::assert_not_impl_any!(RcList<i32>: Send, Sync);
+}
@@ -0,0 +1,175 @@
+// A zipper that always has an element in focus.
+//
+// ListZipper (list_zipper.rs) keeps the focus as the head of its right-hand list, so it can also
+// sit past the last element, or on an empty list, with no focus at all. This one holds the
+// focused element on its own, between the two lists:
+//
+//   [1, 2, 3, 4, 5] focused on 3:   left = [2, 1]   focus = 3   right = [4, 5]
+//
+// `left` is the prefix reversed (nearest element first) and `right` is the suffix, so the
+// neighbours of the focus are both at the head of a list and moving is a pop, a swap and a push:
+// O(1). In exchange a Zipper can't be empty, so List::into_zipper gives None for an empty list,
+// and remove refuses to take away the last element.
+
+use crate::generic_lists::List;
+use std::mem;
+
+pub struct Zipper<T> {
+    left: List<T>,
+    focus: T,
+    right: List<T>,
+}
+
+impl<T> List<T> {
+    // A zipper focused on the head, or None if the list is empty.
+    pub fn into_zipper(mut self) -> Option<Zipper<T>> {
+        let focus = self.pop()?;
+        Some(Zipper { left: List::new(), focus, right: self })
+    }
+}
+
+impl<T> Zipper<T> {
+    pub fn focus(&self) -> &T {
+        &self.focus
+    }
+
+    pub fn focus_mut(&mut self) -> &mut T {
+        &mut self.focus
+    }
+
+    // Replaces the focused element and returns the old one.
+    pub fn set(&mut self, elem: T) -> T {
+        mem::replace(&mut self.focus, elem)
+    }
+
+    // Moves the focus one element towards the start. Returns false, without moving, when the
+    // focus is on the first element.
+    pub fn left(&mut self) -> bool {
+        match self.left.pop() {
+            Some(prev) => {
+                let old = mem::replace(&mut self.focus, prev);
+                self.right.push(old);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Moves the focus one element towards the end. Returns false on the last element.
+    pub fn right(&mut self) -> bool {
+        match self.right.pop() {
+            Some(next) => {
+                let old = mem::replace(&mut self.focus, next);
+                self.left.push(old);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Inserts `elem` just before the focus. The focus stays where it is.
+    pub fn insert_left(&mut self, elem: T) {
+        self.left.push(elem);
+    }
+
+    // Inserts `elem` just after the focus. The focus stays where it is.
+    pub fn insert_right(&mut self, elem: T) {
+        self.right.push(elem);
+    }
+
+    // Removes the focused element. The element after it takes the focus, or the one before it if
+    // the focus was the last element. A zipper is never empty, so when the focus is the only
+    // element left nothing is removed and this returns None (into_list gets it back).
+    pub fn remove(&mut self) -> Option<T> {
+        let next = self.right.pop().or_else(|| self.left.pop())?;
+        Some(mem::replace(&mut self.focus, next))
+    }
+
+    // Zips the list back up, with the elements in their order. O(number of elements left of the
+    // focus), since those have to be moved back in front one by one.
+    pub fn into_list(self) -> List<T> {
+        let Zipper { mut left, focus, mut right } = self;
+        right.push(focus);
+        while let Some(elem) = left.pop() {
+            right.push(elem);
+        }
+        right
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generic_lists::List;
+
+    #[test]
+    fn walk_to_the_end_and_back() {
+        let mut zipper = List::from([1, 2, 3, 4]).into_zipper().unwrap();
+        let mut seen = vec![*zipper.focus()];
+        while zipper.right() {
+            seen.push(*zipper.focus());
+        }
+        assert_eq!(seen, [1, 2, 3, 4]);
+        assert!(!zipper.right());
+        assert_eq!(zipper.focus(), &4);
+
+        let mut seen = vec![*zipper.focus()];
+        while zipper.left() {
+            seen.push(*zipper.focus());
+        }
+        assert_eq!(seen, [4, 3, 2, 1]);
+        assert!(!zipper.left());
+
+        assert!(List::<i32>::new().into_zipper().is_none());
+    }
+
+    #[test]
+    fn edit_in_the_middle() {
+        let mut zipper = List::from(['a', 'b', 'c', 'd']).into_zipper().unwrap();
+        zipper.right();
+        zipper.right();
+        assert_eq!(zipper.set('C'), 'c');
+        zipper.insert_left('x');
+        zipper.insert_right('y');
+        assert_eq!(zipper.focus(), &'C');
+        *zipper.focus_mut() = 'Z';
+
+        assert!(zipper.left());
+        assert_eq!(zipper.focus(), &'x');
+        assert_eq!(zipper.into_list(), ['a', 'b', 'x', 'Z', 'y', 'd']);
+    }
+
+    #[test]
+    fn remove_at_the_boundaries() {
+        // At the start, the next element takes the focus:
+        let mut zipper = List::from([1, 2, 3]).into_zipper().unwrap();
+        assert_eq!(zipper.remove(), Some(1));
+        assert_eq!(zipper.focus(), &2);
+        assert!(!zipper.left());
+
+        // At the end, the previous one does:
+        zipper.right();
+        assert_eq!(zipper.remove(), Some(3));
+        assert_eq!(zipper.focus(), &2);
+        assert!(!zipper.right());
+
+        // The last element stays:
+        assert_eq!(zipper.remove(), None);
+        assert_eq!(zipper.focus(), &2);
+        assert_eq!(zipper.into_list(), [2]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let original: List<i32> = (0..20).collect();
+        for steps in 0..20 {
+            let mut zipper = (0..20).collect::<List<i32>>().into_zipper().unwrap();
+            for _ in 0..steps {
+                assert!(zipper.right());
+            }
+            assert_eq!(zipper.focus(), &steps);
+            let list = zipper.into_list();
+            assert_eq!(list, original.iter().copied().collect::<Vec<_>>());
+            assert_eq!(list.len(), 20);
+        }
+    }
+}
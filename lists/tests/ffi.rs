@@ -0,0 +1,58 @@
+// Uses the list the way a C program would: through symbols declared in an extern "C" block, with
+// nothing but raw pointers and C types crossing the boundary.
+#![cfg(feature = "std")]
+
+// Nothing from the crate is used by name, so it has to be linked in explicitly for the symbols
+// to be found.
+extern crate lists;
+
+use std::ffi::c_void;
+
+extern "C" {
+    fn list_new() -> *mut c_void;
+    fn list_push(lst: *mut c_void, elem: i32);
+    fn list_pop(lst: *mut c_void, out: *mut i32) -> bool;
+    fn list_free(lst: *mut c_void);
+}
+
+#[test]
+fn push_pop_through_the_c_abi() {
+    unsafe {
+        let list = list_new();
+        assert!(!list.is_null());
+
+        let mut out = -1;
+        assert!(!list_pop(list, &mut out));
+        assert_eq!(out, -1);
+
+        list_push(list, 1);
+        list_push(list, 2);
+        list_push(list, 3);
+        assert!(list_pop(list, &mut out));
+        assert_eq!(out, 3);
+        assert!(list_pop(list, &mut out));
+        assert_eq!(out, 2);
+
+        // Freeing a list that still holds elements frees them too:
+        list_push(list, 4);
+        list_free(list);
+    }
+}
+
+#[test]
+fn null_pointers_are_tolerated() {
+    unsafe {
+        let null = std::ptr::null_mut();
+        list_push(null, 1);
+        let mut out = 0;
+        assert!(!list_pop(null, &mut out));
+        list_free(null);
+
+        // A null `out` discards the element:
+        let list = list_new();
+        list_push(list, 5);
+        assert!(list_pop(list, std::ptr::null_mut()));
+        assert!(!list_pop(list, &mut out));
+        list_free(list);
+    }
+}